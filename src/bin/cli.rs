@@ -1,67 +1,286 @@
 use anyhow::Result;
 use askama::Template;
-use std::path::PathBuf;
+use clap::{
+    Args,
+    Parser,
+    Subcommand,
+    ValueEnum,
+};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use yansi::Paint;
 
 use lyric_check::{
+    check::Summary,
+    music::{
+        Mark,
+        Phrasing,
+    },
     Diff,
     DiffPage,
 };
 
+/// Compare lyrics between a script (TXT) and music (MUSICXML)
+#[derive(Parser)]
+#[command(name = "lyric-check", version)]
+struct Cli {
+    /// When to colour the output
+    #[arg(long, global = true, value_enum, default_value_t = When::Auto)]
+    color: When,
+
+    #[command(flatten)]
+    phrasing: PhrasingArgs,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Rules splitting the words of music into phrases
+#[derive(Args)]
+struct PhrasingArgs {
+    /// Characters that end a phrase at the end of a word
+    #[arg(long, global = true, default_value = ".!")]
+    phrase_ends: String,
+
+    /// Rest, in crotchets, long enough to start a phrase (0 for none)
+    #[arg(long, global = true, default_value_t = 2)]
+    phrase_rest: usize,
+
+    /// Do not start a phrase at a capital letter after a rest
+    #[arg(long, global = true)]
+    no_phrase_capitals: bool,
+
+    /// Do not keep the words from `[` to `]` in one phrase
+    #[arg(long, global = true)]
+    no_phrase_groups: bool,
+
+    /// Marks of the score that end a phrase
+    #[arg(long, global = true, value_enum, value_delimiter = ',')]
+    phrase_marks: Vec<PhraseMark>,
+}
+
+#[derive(Clone, Copy)]
+#[derive(ValueEnum)]
+enum PhraseMark {
+    /// Breath mark after a note
+    Breath,
+    /// Fermata over a note
+    Fermata,
+    /// Double or final barline
+    Barline,
+    /// Rehearsal mark
+    Rehearsal,
+}
+
+impl PhrasingArgs {
+    fn phrasing(&self) -> Phrasing {
+        Phrasing {
+            ends: self.phrase_ends.clone(),
+            capitals: !self.no_phrase_capitals,
+            rest: Some(self.phrase_rest).filter(|rest| *rest > 0),
+            groups: !self.no_phrase_groups,
+            marks: self.phrase_marks.iter().map(|mark| match mark {
+                PhraseMark::Breath => Mark::Breath,
+                PhraseMark::Fermata => Mark::Fermata,
+                PhraseMark::Barline => Mark::Barline,
+                PhraseMark::Rehearsal => Mark::Rehearsal,
+            }).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+#[derive(ValueEnum)]
+enum When {
+    /// Colour when writing to a terminal
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print where script line breaks and music phrase boundaries differ
+    Breaks {
+        /// Music (MUSICXML) file
+        music: PathBuf,
+
+        /// Script (TXT) file
+        script: PathBuf,
+
+        /// Words a line break may be from a phrase boundary and still match
+        #[arg(short, long, default_value_t = 0)]
+        tolerance: usize,
+    },
+
+    /// Check every script and music pair in a folder
+    Check {
+        /// Also check all sub-folders
+        #[arg(short, long)]
+        recursive: bool,
+
+        folder: PathBuf,
+    },
+
+    /// Print the differences between music and script
+    Diff {
+        /// Music (MUSICXML) file
+        music: PathBuf,
+
+        /// Script (TXT) file
+        script: PathBuf,
+
+        /// Diff each verse as printed under the staff against the script
+        /// section in the same place, instead of the music as played
+        #[arg(long)]
+        verses: bool,
+    },
+
+    /// Correct single words in the music to match the script
+    FixMusic {
+        /// Music (MUSICXML) file
+        music: PathBuf,
+
+        /// Script (TXT) file
+        script: PathBuf,
+
+        /// Write the corrected music here instead of over the music file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Report the corrections without writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Render the differences between music and script as HTML
+    Html {
+        /// Music (MUSICXML) file
+        music: PathBuf,
+
+        /// Script (TXT) file
+        script: PathBuf,
+    },
+
+    /// Print the lyrics of music, or write them beside each music in a folder
+    Music {
+        #[arg(required_unless_present = "folder")]
+        file: Option<PathBuf>,
+
+        /// Print only this verse (one based) as printed under the staff,
+        /// instead of the music as played
+        #[arg(long, conflicts_with = "folder", value_parser = clap::value_parser!(u8).range(1..=6))]
+        verse: Option<u8>,
+
+        /// Write a script (TXT) for each music (MUSICXML) in the folder
+        #[arg(long, conflicts_with = "file")]
+        folder: Option<PathBuf>,
+
+        /// Skip music in the folder named before this
+        #[arg(long, requires = "folder")]
+        from: Option<String>,
+
+        /// Leave scripts that already exist alone
+        #[arg(long, requires = "folder")]
+        no_clobber: bool,
+
+        /// Write each script as `<name><suffix>.txt`, e.g. `.draft`
+        #[arg(long, requires = "folder")]
+        suffix: Option<String>,
+    },
+
+    /// Print the bars of music in the order they are played
+    Roadmap {
+        /// Music (MUSICXML) file
+        music: PathBuf,
+    },
+
+    /// Print the words of a script
+    Script {
+        file: PathBuf,
+    },
+}
+
 fn main() -> Result<()> {
-    let args: Vec<String> = std::env::args().skip(1).collect();
-    let args: Vec<&str> = args.iter().map(String::as_ref).collect();
-    match &args[..] {
-        ["html", music, script] => {
+    let cli = Cli::parse();
+    let phrasing = cli.phrasing.phrasing();
+    match cli.color {
+        When::Auto if std::io::stdout().is_terminal() =>
+            yansi::enable(),
+
+        When::Auto | When::Never =>
+            yansi::disable(),
+
+        When::Always =>
+            yansi::enable(),
+    }
+    match cli.command {
+        Command::Html { music, script } => {
             let xml = std::fs::read_to_string(music)?;
             let txt = std::fs::read_to_string(script)?;
             let page = DiffPage {
                 error: None,
                 folder: String::new(),
+                breadcrumbs: Vec::new(),
                 sections: lyric_check::diff::read(&txt, &xml)?,
+                side_by_side: false,
+                layouts: Vec::new(),
+                views: Vec::new(),
+                edit: None,
+                events: None,
             };
             let html = page.render()?;
             println!("{html}");
         }
 
-        ["diff", music, script] => {
+        Command::Breaks { music, script, tolerance } => {
+            let xml = std::fs::read_to_string(music)?;
+            let txt = std::fs::read_to_string(script)?;
+            for mismatch in lyric_check::breaks::read(&txt, &xml, &phrasing, tolerance)? {
+                println!("{:>4} bar {:<4} {}: {}",
+                    mismatch.line,
+                    mismatch.bar,
+                    mismatch.kind,
+                    mismatch.context,
+                );
+            }
+        }
+
+        Command::Diff { music, script, verses } => {
             let xml = std::fs::read_to_string(music)?;
             let txt = std::fs::read_to_string(script)?;
-            let sections = lyric_check::diff::read(&txt, &xml)?;
+            let sections = if verses {
+                lyric_check::diff::read_verses(&txt, &xml)?
+            } else {
+                lyric_check::diff::read(&txt, &xml)?
+            };
             for section in sections {
-                println!("{}", section.heading);
+                let music: Vec<String> = section.music.iter()
+                    .map(|heading| format!("  [{heading}]"))
+                    .collect();
+                println!("{}{}", section.heading, music.concat());
                 for line in section.lines {
-                    println!("{}", line.number);
-                    for diff in line.diffs {
-                        match diff {
-                            Diff::Same(text) =>
-                                println!("    {text}"),
-
-                            Diff::Music(text) =>
-                                println!("    ==> {text}"),
-
-                            Diff::Script(text) =>
-                                println!("    {text} <=="),
-
-                            Diff::Case(text) =>
-                                println!("    << {text} >>"),
-
-                            Diff::Replace(replace) =>
-                                println!("    {} <=> {}", replace.script, replace.music),
-                        }
-                    }
+                    println!("{:>4} {}", line.number, diff_line(&line.diffs));
                 }
             }
         }
 
-        ["music", "--folder", folder, "--from", from] =>
-            music_folder(folder, Some(from))?,
+        Command::FixMusic { music, script, output, dry_run } =>
+            fix_music(&music, &script, output.as_deref(), dry_run)?,
 
-        ["music", "--folder", folder] =>
-            music_folder(folder, None)?,
+        Command::Check { recursive, folder } =>
+            check(&folder, recursive)?,
 
-        ["music", file] => {
+        Command::Music { folder: Some(folder), from, no_clobber, suffix, .. } =>
+            music_folder(&folder, &phrasing, from.as_deref(), no_clobber, suffix.as_deref())?,
+
+        Command::Music { file: Some(file), verse, .. } => {
             let xml = std::fs::read_to_string(file)?;
-            if let Some(music) = lyric_check::music::read(&xml)? {
+            let music = match verse {
+                Some(verse) => lyric_check::music::read_verse(&xml, usize::from(verse - 1), &phrasing)?,
+                None => lyric_check::music::read_with(&xml, &phrasing)?,
+            };
+            if let Some(music) = music {
                 if let Some(title) = music.title {
                     println!(" {title}");
                     println!("{}", "=".repeat(2 + title.len()));
@@ -76,24 +295,198 @@ fn main() -> Result<()> {
             }
         }
 
-        ["script", file] => {
+        Command::Music { .. } =>
+            unreachable!("clap requires a file or folder"),
+
+        Command::Roadmap { music } => {
+            let xml = std::fs::read_to_string(music)?;
+            let roadmap: Vec<String> = lyric_check::music::roadmap(&xml)?.iter()
+                .map(|segment| segment.to_string())
+                .collect();
+            println!("{}", roadmap.join(", "));
+        }
+
+        Command::Script { file } => {
             let txt = std::fs::read_to_string(file)?;
             let words = lyric_check::script::read(&txt)?;
             for word in words {
                 println!("{word:?}");
             }
         }
+    }
+    Ok(())
+}
+
+/// Inline rendering of a line of diffs, coloured to match the web page,
+/// with markers in place of colour when it is disabled
+fn diff_line(diffs: &[Diff]) -> String {
+    let colour = yansi::is_enabled();
+    let mut line = Vec::with_capacity(diffs.len());
+    for diff in diffs {
+        line.push(match diff {
+            Diff::Same(text) =>
+                text.clone(),
+
+            Diff::Music(text) if colour =>
+                text.red().bold().to_string(),
+
+            Diff::Music(text) =>
+                format!("[==> {text}]"),
+
+            Diff::Script(text) if colour =>
+                text.blue().bold().to_string(),
+
+            Diff::Script(text) =>
+                format!("[{text} <==]"),
 
-        _ => {
-            println!("lyric-check {args:?}");
+            Diff::Case(text) if colour =>
+                text.magenta().bold().to_string(),
+
+            Diff::Case(text) =>
+                format!("[<< {text} >>]"),
+
+            Diff::Replace(replace) if colour =>
+                format!("{}/{}",
+                    replace.script.blue().bold(),
+                    replace.music.red().bold(),
+                ),
+
+            Diff::Replace(replace) =>
+                format!("[{} <=> {}]", replace.script, replace.music),
+        });
+    }
+    line.join(" ")
+}
+
+#[derive(Default)]
+struct Check {
+    checked: usize,
+    different: usize,
+    summary: Summary,
+    unpaired: Vec<PathBuf>,
+    failed: Vec<(PathBuf, anyhow::Error)>,
+}
+
+fn check(folder: &Path, recursive: bool) -> Result<()> {
+    let mut report = Check::default();
+    check_folder(&mut report, folder, recursive)?;
+
+    println!();
+    println!("{} pairs checked, {} with differences", report.checked, report.different);
+    println!("    {}", check_summary(&report.summary));
+    if !report.unpaired.is_empty() {
+        println!();
+        println!("Unpaired");
+        for path in &report.unpaired {
+            println!("    {}", path.display());
+        }
+    }
+    if !report.failed.is_empty() {
+        println!();
+        println!("Failed");
+        for (path, error) in &report.failed {
+            println!("    {}", path.display());
+            println!("        {error:#}");
         }
     }
     Ok(())
 }
 
-fn music_folder(folder: &str, from: Option<&str>) -> Result<()> {
-    use std::io::Write;
+fn check_folder(report: &mut Check, folder: &Path, recursive: bool) -> Result<()> {
+    let dir = lyric_check::check::Folder::read(folder)?;
+
+    let pairs = lyric_check::check::pair(&dir.scripts, &dir.musics);
+    for (script, music) in pairs.pairs {
+        let script = folder.join(format!("{script}.txt"));
+        let music = folder.join(format!("{music}.musicxml"));
+        match check_pair(&script, &music) {
+            Ok(summary) => {
+                report.checked += 1;
+                if summary.differences() > 0 {
+                    report.different += 1;
+                }
+                report.summary.add(&summary);
+                println!("{}", script.with_extension("").display());
+                println!("    {}", check_summary(&summary));
+            }
 
+            Err(error) =>
+                report.failed.push((music, error)),
+        }
+    }
+    for script in pairs.scripts {
+        report.unpaired.push(folder.join(format!("{script}.txt")));
+    }
+    for music in pairs.musics {
+        report.unpaired.push(folder.join(format!("{music}.musicxml")));
+    }
+
+    if recursive {
+        for name in dir.folders {
+            check_folder(report, &folder.join(name), recursive)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_pair(script: &Path, music: &Path) -> Result<Summary> {
+    use anyhow::Context;
+
+    let txt = std::fs::read_to_string(script)
+        .with_context(|| format!("Read from {}", script.display()))?;
+    let xml = std::fs::read_to_string(music)
+        .with_context(|| format!("Read from {}", music.display()))?;
+    let sections = lyric_check::diff::read(&txt, &xml)?;
+    Ok(Summary::new(&sections))
+}
+
+fn check_summary(summary: &Summary) -> String {
+    format!("{} same, {} case, {} replaced, {} missing, {} extra",
+        summary.same,
+        summary.case,
+        summary.replace,
+        summary.script,
+        summary.music,
+    )
+}
+
+fn fix_music(
+    music: &Path,
+    script: &Path,
+    output: Option<&Path>,
+    dry_run: bool,
+) -> Result<()> {
+    let xml = std::fs::read_to_string(music)?;
+    let txt = std::fs::read_to_string(script)?;
+    let fixed = lyric_check::fix::music(&txt, &xml)?;
+
+    for edit in &fixed.edits {
+        println!("{:>6}: {} ==> {}", edit.line, edit.music, edit.script);
+    }
+    if !fixed.skipped.is_empty() {
+        println!();
+        println!("Skipped");
+        for skipped in &fixed.skipped {
+            let line = skipped.line.map(|line| line.to_string()).unwrap_or_default();
+            println!("{line:>6}: {} <=> {} ({})", skipped.music, skipped.script, skipped.reason);
+        }
+    }
+    println!();
+    println!("{} words corrected, {} skipped", fixed.edits.len(), fixed.skipped.len());
+
+    if !dry_run && !fixed.edits.is_empty() {
+        std::fs::write(output.unwrap_or(music), fixed.xml)?;
+    }
+    Ok(())
+}
+
+fn music_folder(
+    folder: &Path,
+    phrasing: &Phrasing,
+    from: Option<&str>,
+    no_clobber: bool,
+    suffix: Option<&str>,
+) -> Result<()> {
     let folder = music_folder_list(folder)?;
     for (name, path) in folder {
         if let Some(from) = &from {
@@ -101,26 +494,23 @@ fn music_folder(folder: &str, from: Option<&str>) -> Result<()> {
                 continue;
             }
         }
+        let txt = path.with_file_name(format!("{name}{}.txt", suffix.unwrap_or("")));
+        if no_clobber && txt.exists() {
+            println!(" ==> {name} (skipped, {} exists)", txt.display());
+            continue;
+        }
         println!(" ==> {name}");
         let xml = std::fs::read_to_string(&path)?;
-        if let Some(music) = lyric_check::music::read(&xml)? {
-            let mut file = std::fs::File::create(path.with_extension("txt"))?;
+        if let Some(music) = lyric_check::music::read_with(&xml, phrasing)? {
             let title = music.title.unwrap_or(name);
-            writeln!(file, " {title}")?;
-            writeln!(file, "{}", "=".repeat(2 + title.len()))?;
-            writeln!(file, "")?;
-            for phrase in &music.phrases {
-                for word in &phrase.words {
-                    write!(file, "{} ", word.text)?;
-                }
-                writeln!(file, "")?;
-            }
+            let draft = lyric_check::script::draft(&title, &music.phrases);
+            std::fs::write(txt, draft)?;
         }
     }
     Ok(())
 }
 
-fn music_folder_list(folder: &str) -> Result<Vec<(String, PathBuf)>> {
+fn music_folder_list(folder: &Path) -> Result<Vec<(String, PathBuf)>> {
     let mut files = Vec::new();
 
     for entry in std::fs::read_dir(folder)? {
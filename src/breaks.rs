@@ -0,0 +1,224 @@
+use anyhow::Result;
+use ::diff::Result as Side;
+
+use super::{
+    diff::key,
+    music,
+    script,
+};
+
+/// Line break of a script that does not coincide with a phrase boundary
+/// of the music, or the other way round
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct Mismatch {
+    pub kind: Kind,
+    /// Number of the script line the break is before, or within
+    pub line: String,
+    /// Line of the script (zero based) the number starts
+    pub index: Option<usize>,
+    /// Bar of the music word after the break
+    pub bar: usize,
+    /// Music words either side of the break, like `world. / The`
+    pub context: String,
+}
+
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum Kind {
+    /// Script line breaks within a music phrase
+    Script,
+    /// Music phrase ends within a script line
+    Music,
+}
+
+impl std::fmt::Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Kind::Script =>
+                write!(f, "line breaks within a phrase"),
+
+            Kind::Music =>
+                write!(f, "phrase ends within the line"),
+        }
+    }
+}
+
+/// Breaks between lines of the script and phrases of the music with no
+/// counterpart within `tolerance` words, once their words are aligned
+pub fn read(
+    txt: &str,
+    xml: &str,
+    phrasing: &music::Phrasing,
+    tolerance: usize,
+) -> Result<Vec<Mismatch>> {
+    let Some(music) = music::read_with(xml, phrasing)? else {
+        anyhow::bail!("No lyrics in music")
+    };
+    let script = script::read(txt)?;
+    Ok(compare(&script, &music.phrases, tolerance))
+}
+
+/// Script line of a word
+#[derive(Clone)]
+struct Line {
+    number: String,
+    index: Option<usize>,
+}
+
+fn compare(script: &[script::Word], phrases: &[music::Phrase], tolerance: usize) -> Vec<Mismatch> {
+    let mut musics = Vec::new();
+    let mut music_breaks = Vec::new();
+    for phrase in phrases {
+        if !musics.is_empty() {
+            music_breaks.push(musics.len());
+        }
+        musics.extend(phrase.words.iter());
+    }
+    let music_keys: Vec<String> = musics.iter()
+        .map(|word| key(&word.text))
+        .collect();
+
+    let mut scripts = Vec::new();
+    let mut lines = Vec::new();
+    let mut line_starts = Vec::new();
+    let mut line = Line {
+        number: String::new(),
+        index: None,
+    };
+    for word in script {
+        match word {
+            script::Word::Heading(_) =>
+                (),
+
+            script::Word::Line { number, index } => {
+                line = Line {
+                    number: number.clone(),
+                    index: Some(*index),
+                };
+                if !scripts.is_empty() && line_starts.last() != Some(&scripts.len()) {
+                    line_starts.push(scripts.len());
+                }
+            }
+
+            script::Word::Word(text) => {
+                scripts.push(key(text));
+                lines.push(line.clone());
+            }
+        }
+    }
+
+    // script line at each music position, and music position of each
+    // script line break
+    let mut music_lines = Vec::with_capacity(musics.len() + 1);
+    let mut script_breaks = Vec::new();
+    let (mut s, mut m) = (0, 0);
+    for diff in ::diff::slice(&scripts, &music_keys) {
+        if !matches!(diff, Side::Right(_)) && line_starts.contains(&s) {
+            script_breaks.push((m, lines[s].clone()));
+        }
+        match diff {
+            Side::Both(..) => {
+                music_lines.push(lines[s].clone());
+                s += 1;
+                m += 1;
+            }
+
+            Side::Left(_) =>
+                s += 1,
+
+            Side::Right(_) => {
+                let line = lines.get(s).or(lines.last()).cloned().unwrap_or(line.clone());
+                music_lines.push(line);
+                m += 1;
+            }
+        }
+    }
+
+    let near = |position: usize, breaks: &[usize]| {
+        breaks.iter().any(|other| position.abs_diff(*other) <= tolerance)
+    };
+    let mismatch = |kind, position: usize, line: &Line| Mismatch {
+        kind,
+        line: line.number.clone(),
+        index: line.index,
+        bar: musics.get(position).or(musics.last()).map_or(0, |word| word.bar),
+        context: format!("{} / {}",
+            position.checked_sub(1).and_then(|before| musics.get(before)).map_or("", |word| &word.text),
+            musics.get(position).map_or("", |word| &word.text),
+        ),
+    };
+
+    let script_positions: Vec<usize> = script_breaks.iter()
+        .map(|(position, _)| *position)
+        .collect();
+    let mut mismatches = Vec::new();
+    for (position, line) in &script_breaks {
+        if !near(*position, &music_breaks) {
+            mismatches.push((*position, mismatch(Kind::Script, *position, line)));
+        }
+    }
+    for position in &music_breaks {
+        if !near(*position, &script_positions) {
+            let line = &music_lines[*position];
+            mismatches.push((*position, mismatch(Kind::Music, *position, line)));
+        }
+    }
+    mismatches.sort_by_key(|(position, _)| *position);
+    mismatches.into_iter().map(|(_, mismatch)| mismatch).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::music::{
+        Phrase,
+        Word,
+    };
+
+    fn phrase(bar: usize, words: &[&str]) -> Phrase {
+        Phrase {
+            start: 0,
+            end: 0,
+            words: words.iter().map(|text| Word {
+                start: 0,
+                end: 0,
+                text: String::from(*text),
+                bar,
+                verse: None,
+                lyrics: Vec::new(),
+            }).collect(),
+        }
+    }
+
+    #[test]
+    fn test_compare() -> Result<()> {
+        let phrases = vec![
+            phrase(1, &["Hello", "world."]),
+            phrase(2, &["Goodbye", "cruel", "world."]),
+            phrase(3, &["The", "end!"]),
+        ];
+        let script = script::read("Song\n1. Hello world. Goodbye\n2. cruel world.\n3. The end!")?;
+
+        let mismatches = compare(&script, &phrases, 0);
+        assert_eq!(mismatches, vec![
+            Mismatch {
+                kind: Kind::Music,
+                line: String::from("1."),
+                index: Some(1),
+                bar: 2,
+                context: String::from("world. / Goodbye"),
+            },
+            Mismatch {
+                kind: Kind::Script,
+                line: String::from("2."),
+                index: Some(2),
+                bar: 2,
+                context: String::from("Goodbye / cruel"),
+            },
+        ]);
+
+        assert_eq!(compare(&script, &phrases, 1), vec![]);
+        Ok(())
+    }
+}
@@ -0,0 +1,200 @@
+use std::path::Path;
+
+use super::Section;
+
+/// Folders, scripts and musics of a folder, by name
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq)]
+pub struct Folder {
+    pub folders: Vec<String>,
+    pub scripts: Vec<String>,
+    pub musics: Vec<String>,
+}
+
+impl Folder {
+    /// Names in a folder, leaving out any that are not UTF-8
+    pub fn read(folder: &Path) -> std::io::Result<Self> {
+        let mut answer = Folder::default();
+
+        for entry in std::fs::read_dir(folder)? {
+            let entry = entry?;
+            let Some(name) = entry.file_name().to_str().map(String::from) else {
+                continue;
+            };
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                answer.folders.push(name);
+            } else if file_type.is_file() {
+                if let Some(name) = name.strip_suffix(".txt") {
+                    answer.scripts.push(String::from(name));
+                }
+                if let Some(name) = name.strip_suffix(".musicxml") {
+                    answer.musics.push(String::from(name));
+                }
+            }
+        }
+
+        answer.folders.sort();
+        answer.scripts.sort();
+        answer.musics.sort();
+
+        Ok(answer)
+    }
+}
+
+/// Scripts and musics of a folder paired up by name
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq)]
+pub struct Pairs {
+    pub pairs: Vec<(String, String)>,
+    pub scripts: Vec<String>,
+    pub musics: Vec<String>,
+}
+
+/// Pair scripts and musics with the same name, falling back to the
+/// single script and single music convention for whatever is left over.
+pub fn pair(scripts: &[String], musics: &[String]) -> Pairs {
+    let mut answer = Pairs::default();
+
+    for script in scripts {
+        if musics.contains(script) {
+            answer.pairs.push((script.clone(), script.clone()));
+        } else {
+            answer.scripts.push(script.clone());
+        }
+    }
+    for music in musics {
+        if !scripts.contains(music) {
+            answer.musics.push(music.clone());
+        }
+    }
+
+    if let ([script], [music]) = (&answer.scripts[..], &answer.musics[..]) {
+        answer.pairs.push((script.clone(), music.clone()));
+        answer.scripts.clear();
+        answer.musics.clear();
+    }
+
+    answer
+}
+
+/// Count of words in each kind of `Diff`, a word of the script paired
+/// with one of the music counted once however many parts of it differ
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Clone, Copy)]
+#[derive(PartialEq)]
+pub struct Summary {
+    pub same: usize,
+    pub case: usize,
+    pub replace: usize,
+    pub music: usize,
+    pub script: usize,
+}
+
+impl Summary {
+    pub fn new(sections: &[Section]) -> Self {
+        let mut summary = Summary::default();
+        for section in sections {
+            summary.add(&section.summary);
+        }
+        summary
+    }
+
+    pub fn add(&mut self, other: &Summary) {
+        self.same += other.same;
+        self.case += other.case;
+        self.replace += other.replace;
+        self.music += other.music;
+        self.script += other.script;
+    }
+
+    pub fn differences(&self) -> usize {
+        self.case + self.replace + self.music + self.script
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| String::from(*name)).collect()
+    }
+
+    #[test]
+    fn test_folder_read() -> std::io::Result<()> {
+        let path = std::env::temp_dir()
+            .join(format!("lyric-check-{}-folder", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(path.join("Act 2"))?;
+        std::fs::create_dir_all(path.join("Act 1"))?;
+        for name in ["Two.txt", "One.txt", "One.musicxml", "Notes.md"] {
+            std::fs::write(path.join(name), "")?;
+        }
+
+        let folder = Folder::read(&path);
+        std::fs::remove_dir_all(&path)?;
+        assert_eq!(folder?, Folder {
+            folders: strings(&["Act 1", "Act 2"]),
+            scripts: strings(&["One", "Two"]),
+            musics: strings(&["One"]),
+        });
+        Ok(())
+    }
+
+    #[test]
+    fn test_pair_by_name() {
+        let pairs = pair(
+            &strings(&["One", "Two", "Three"]),
+            &strings(&["Four", "One", "Two"]),
+        );
+        assert_eq!(pairs, Pairs {
+            pairs: vec![
+                (String::from("One"), String::from("One")),
+                (String::from("Two"), String::from("Two")),
+                (String::from("Three"), String::from("Four")),
+            ],
+            scripts: Vec::new(),
+            musics: Vec::new(),
+        });
+    }
+
+    #[test]
+    fn test_summary_counts_words() -> anyhow::Result<()> {
+        let xml = concat!(
+            "<score-partwise><part id=\"P1\"><measure number=\"1\">",
+            "<note><duration>256</duration><voice>1</voice>",
+            "<lyric number=\"verse1\"><syllabic>single</syllabic><text>Hello</text></lyric></note>",
+            "<note><duration>256</duration><voice>1</voice>",
+            "<lyric number=\"verse1\"><syllabic>single</syllabic><text>world</text></lyric></note>",
+            "</measure></part></score-partwise>",
+        );
+        let sections = crate::diff::read("Song\n1. hello world,\n", xml)?;
+        assert_eq!(Summary::new(&sections), Summary {
+            same: 0,
+            case: 1,
+            replace: 1,
+            music: 0,
+            script: 0,
+        });
+        Ok(())
+    }
+
+    #[test]
+    fn test_pair_unpaired() {
+        let pairs = pair(
+            &strings(&["One", "Three", "Two"]),
+            &strings(&["Four", "Five", "One"]),
+        );
+        assert_eq!(pairs, Pairs {
+            pairs: vec![
+                (String::from("One"), String::from("One")),
+            ],
+            scripts: strings(&["Three", "Two"]),
+            musics: strings(&["Four", "Five"]),
+        });
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::Context;
+use rocket::tokio;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use lyric_check::check::{
+    self,
+    Folder,
+    Summary,
+};
+
+/// Summary of the diff of a script and music paired in a folder
+pub struct Row {
+    /// Folder within the root (empty for the root)
+    pub folder: String,
+    pub script: String,
+    pub music: String,
+    pub summary: Result<Summary, String>,
+}
+
+/// Summaries of every pair under the root, each diffed again only once
+/// its script or music is modified
+pub struct Dashboard {
+    cache: Mutex<HashMap<(PathBuf, PathBuf), Entry>>,
+}
+
+struct Entry {
+    modified: (SystemTime, SystemTime),
+    summary: Result<Summary, String>,
+}
+
+impl Dashboard {
+    pub fn new() -> Self {
+        Dashboard {
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn rows(&self, root: &Path) -> Vec<Row> {
+        let mut rows = Vec::new();
+        let mut seen = Vec::new();
+        let mut folders = vec![String::new()];
+        while let Some(folder) = folders.pop() {
+            let path = root.join(&folder);
+            let Ok(Ok(dir)) = tokio::task::spawn_blocking(move || Folder::read(&path)).await else {
+                continue;
+            };
+            for name in dir.folders.iter().rev() {
+                folders.push(if folder.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{folder}/{name}")
+                });
+            }
+            for (script, music) in check::pair(&dir.scripts, &dir.musics).pairs {
+                let paths = (
+                    root.join(&folder).join(format!("{script}.txt")),
+                    root.join(&folder).join(format!("{music}.musicxml")),
+                );
+                let summary = self.summary(&paths).await;
+                seen.push(paths);
+                rows.push(Row {
+                    folder: folder.clone(),
+                    script,
+                    music,
+                    summary,
+                });
+            }
+        }
+        self.cache.lock().unwrap().retain(|paths, _| seen.contains(paths));
+        rows
+    }
+
+    async fn summary(&self, paths: &(PathBuf, PathBuf)) -> Result<Summary, String> {
+        let modified = match (modified(&paths.0).await, modified(&paths.1).await) {
+            (Ok(script), Ok(music)) => (script, music),
+            (Err(error), _) | (_, Err(error)) => return Err(format!("{error:#}")),
+        };
+        if let Some(entry) = self.cache.lock().unwrap().get(paths) {
+            if entry.modified == modified {
+                return entry.summary.clone();
+            }
+        }
+        let summary = diff(&paths.0, &paths.1).await
+            .map_err(|error| format!("{error:#}"));
+        self.cache.lock().unwrap().insert(paths.clone(), Entry {
+            modified,
+            summary: summary.clone(),
+        });
+        summary
+    }
+}
+
+async fn modified(path: &Path) -> anyhow::Result<SystemTime> {
+    let meta = tokio::fs::metadata(path).await
+        .with_context(|| format!("Read from {}", path.display()))?;
+    Ok(meta.modified()?)
+}
+
+async fn diff(script: &Path, music: &Path) -> anyhow::Result<Summary> {
+    let txt = tokio::fs::read_to_string(script).await
+        .with_context(|| format!("Read from {}", script.display()))?;
+    let xml = tokio::fs::read_to_string(music).await
+        .with_context(|| format!("Read from {}", music.display()))?;
+    let sections = lyric_check::diff::read(&txt, &xml)?;
+    Ok(Summary::new(&sections))
+}
+
+/// Columns the dashboard can be sorted by, as named in the query
+pub const SORTS: [(&str, &str); 6] = [
+    ("song", "Song"),
+    ("same", "Same"),
+    ("case", "Case"),
+    ("replaced", "Replaced"),
+    ("missing", "Missing"),
+    ("extra", "Extra"),
+];
+
+/// Sort rows by a column of `SORTS`, most words first with failures
+/// last, or else by song.
+pub fn sort(rows: &mut [Row], by: &str) {
+    let count: fn(&Summary) -> usize = match by {
+        "same" => |summary| summary.same,
+        "case" => |summary| summary.case,
+        "replaced" => |summary| summary.replace,
+        "missing" => |summary| summary.script,
+        "extra" => |summary| summary.music,
+        _ => {
+            rows.sort_by(|a, b| (&a.folder, &a.script).cmp(&(&b.folder, &b.script)));
+            return;
+        }
+    };
+    rows.sort_by_key(|row| match &row.summary {
+        Ok(summary) => (false, std::cmp::Reverse(count(summary))),
+        Err(_) => (true, std::cmp::Reverse(0)),
+    });
+}
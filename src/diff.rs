@@ -1,7 +1,9 @@
 use anyhow::Result;
 use diff::Result as Side;
+use std::ops::Range;
 
 use super::{
+    check::Summary,
     music,
     script,
     Diff,
@@ -16,18 +18,320 @@ pub fn read(txt: &str, xml: &str) -> Result<Vec<Section>> {
         anyhow::bail!("No lyrics in music")
     };
     let script = script::read(txt)?;
+    let mappings = script::front_matter(txt)?;
+    let roadmap = music::roadmap(xml)?;
 
-    let music_words = music_words(&music);
-    let script_words = script_words(&script);
+    Ok(align(&script, &music, &mappings, &roadmap).into_iter()
+        .flat_map(Builder::build)
+        .collect())
+}
+
+/// Diff of each script section mapped to a portion of the music against
+/// that portion, and of the sections between against the music played
+/// between, ready to build. A section is mapped by the front matter, or
+/// else by a `Verse N` heading to the Nth pass through the next repeat.
+/// A mapped section with no music counterpart is left as all script.
+fn align<'stack>(
+    script: &'stack [script::Word],
+    music: &'stack music::Music,
+    mappings: &[script::Mapping],
+    roadmap: &[music::Segment],
+) -> Vec<Builder<'stack>> {
+    let words: Vec<&music::Word> = music.phrases.iter()
+        .flat_map(|phrase| phrase.words.iter())
+        .collect();
+
+    let mut claimed = vec![false; words.len()];
+    let mut selections = Vec::new();
+    let mut cursor = 0;
+    for section in script_sections(script) {
+        let Some(Script::Heading(heading)) = script.get(section.start) else {
+            continue;
+        };
+        let Some(selection) = select(heading, mappings, roadmap, &words, &claimed, cursor) else {
+            continue;
+        };
+        for index in &selection {
+            claimed[*index] = true;
+        }
+        if let Some(last) = selection.last() {
+            cursor = last + 1;
+        }
+        selections.push((section, selection));
+    }
+
+    // music played after a section, before the music of the next script,
+    // is sung in it
+    let mut items: Vec<(Range<usize>, Vec<&music::Word>)> = Vec::new();
+    let mut last: Option<usize> = None;
+    let mut emitted = vec![false; words.len()];
+    let mut from = 0;
+    let mut cursor = 0;
+    for (section, selection) in selections {
+        let start = selection.first().copied().unwrap_or(cursor);
+        let between: Vec<&music::Word> = (cursor..start)
+            .filter(|index| !claimed[*index])
+            .inspect(|index| emitted[*index] = true)
+            .map(|index| words[index])
+            .collect();
+        match last {
+            Some(item) if from == section.start =>
+                items[item].1.extend(between),
+
+            _ if from < section.start => {
+                last = Some(items.len());
+                items.push((from..section.start, between));
+            }
+
+            _ if !between.is_empty() =>
+                items.push((from..section.start, between)),
+
+            _ =>
+                (),
+        }
 
-    let mut builder = Builder::new();
+        if let Some(index) = selection.last() {
+            cursor = cursor.max(index + 1);
+            last = Some(items.len());
+        }
+        items.push((section.clone(), selection.iter().map(|index| words[*index]).collect()));
+        from = section.end;
+    }
+    let rest: Vec<&music::Word> = (0..words.len())
+        .filter(|index| !claimed[*index] && !emitted[*index])
+        .map(|index| words[index])
+        .collect();
+    match last {
+        Some(item) if from == script.len() =>
+            items[item].1.extend(rest),
+
+        _ if from < script.len() || !rest.is_empty() =>
+            items.push((from..script.len(), rest)),
+
+        _ =>
+            (),
+    }
+
+    items.into_iter()
+        .map(|(section, portion)| compare_words(&script[section], &portion, &music.headings))
+        .collect()
+}
+
+/// Indexes of the words of the music a section heading is mapped to, in
+/// the order they are played, leaving out words already claimed
+fn select(
+    heading: &str,
+    mappings: &[script::Mapping],
+    roadmap: &[music::Segment],
+    words: &[&music::Word],
+    claimed: &[bool],
+    cursor: usize,
+) -> Option<Vec<usize>> {
+    let heading = heading.trim();
+    let (bars, verse) = match mappings.iter().find(|mapping| mapping.heading.eq_ignore_ascii_case(heading)) {
+        Some(mapping) =>
+            (mapping.bars.clone(), mapping.verse),
+
+        None => {
+            // unless the music has no repeats
+            let verse = verse_number(heading)?;
+            if words.iter().all(|word| word.verse.is_none()) {
+                return None;
+            }
+            (None, Some(verse))
+        }
+    };
+    let selected = |index: &usize| {
+        let word = words[*index];
+        !claimed[*index] && bars.as_ref().is_none_or(|bars| bars.contains(&word.bar))
+    };
+    let selection = match verse {
+        // the pass through the repeat the last section ended in, or else
+        // through the next repeat, and not the same pass of every repeat
+        Some(verse) => {
+            let repeats: Vec<Option<usize>> = words.iter()
+                .map(|word| repeat_of(word, roadmap))
+                .collect();
+            let from = cursor.checked_sub(1)
+                .and_then(|index| repeats[index])
+                .or_else(|| repeats.iter().skip(cursor).find_map(|repeat| *repeat));
+            let Some(from) = from else {
+                return Some(Vec::new());
+            };
+            let passes: Vec<usize> = (0..words.len())
+                .filter(selected)
+                .filter(|index| in_pass(words[*index], verse, roadmap))
+                .collect();
+            let repeat = passes.iter()
+                .filter_map(|index| repeats[*index])
+                .find(|repeat| from <= *repeat);
+            passes.into_iter()
+                .filter(|index| repeat.is_some() && repeats[*index] == repeat)
+                .collect()
+        }
+
+        // the next time the bars are played
+        None =>
+            (cursor..words.len())
+                .skip_while(|index| !selected(index))
+                .take_while(selected)
+                .collect(),
+    };
+    Some(selection)
+}
+
+/// Verse (zero based) of a heading like `Verse 2`
+fn verse_number(heading: &str) -> Option<usize> {
+    let heading = heading.to_ascii_lowercase();
+    let number = heading.strip_prefix("verse")?
+        .trim()
+        .trim_end_matches([':', '.']);
+    match number.parse::<usize>() {
+        Ok(number) if number > 0 => Some(number - 1),
+        _ => None,
+    }
+}
+
+/// Whether a word is sung on a pass through a repeat, in the repeated
+/// bars or in the alternative ending for the pass
+fn in_pass(word: &music::Word, verse: usize, roadmap: &[music::Segment]) -> bool {
+    match word.verse {
+        Some(pass) =>
+            pass == verse,
+
+        None =>
+            roadmap.iter().any(|segment| {
+                segment.ending.as_ref().is_some_and(|ending| ending.contains(verse))
+                    && (segment.first..=segment.last).contains(&word.bar)
+            }),
+    }
+}
+
+/// Repeat written outside any other that a word is sung within, if any
+fn repeat_of(word: &music::Word, roadmap: &[music::Segment]) -> Option<usize> {
+    roadmap.iter()
+        .find(|segment| segment.verse == word.verse && (segment.first..=segment.last).contains(&word.bar))
+        .and_then(|segment| segment.repeat)
+}
+
+/// Diff of each verse of the music, as printed under the staff, against
+/// the script section in the same place: the first section with lines
+/// against the first verse, and so on
+pub fn read_verses(txt: &str, xml: &str) -> Result<Vec<Section>> {
+    let phrasing = music::Phrasing::default();
+    let Some(music) = music::read_verse(xml, 0, &phrasing)? else {
+        anyhow::bail!("No lyrics in music")
+    };
+    let script = script::read(txt)?;
+    let scripts = script_verses(&script);
+
+    let mut sections = Vec::new();
+    for verse in 0..music.verses.max(scripts.len()) {
+        let music = if verse < music.verses {
+            music::read_verse(xml, verse, &phrasing)?
+        } else {
+            None
+        };
+        let music = music.unwrap_or(music::Music {
+            title: None,
+            phrases: Vec::new(),
+            verses: 0,
+            headings: Vec::new(),
+        });
+        let script = scripts.get(verse).copied().unwrap_or_default();
+        let mut verse_sections = compare(script, &music).build();
+        match verse_sections.first_mut() {
+            Some(section) if !section.heading.trim().is_empty() =>
+                (),
+
+            Some(section) =>
+                section.heading = format!("Verse {}", verse + 1),
+
+            None =>
+                (),
+        }
+        sections.extend(verse_sections);
+    }
+    Ok(sections)
+}
+
+/// Sections of a script that have lines, each from its heading
+fn script_verses(script: &[script::Word]) -> Vec<&[script::Word]> {
+    script_sections(script).into_iter()
+        .map(|section| &script[section])
+        .filter(|section| section.iter().any(|word| matches!(word, Script::Line { .. })))
+        .collect()
+}
+
+/// Sections of a script, each from a heading that is not blank
+fn script_sections(script: &[script::Word]) -> Vec<Range<usize>> {
+    let mut sections = Vec::new();
+    let mut from = 0;
+    for (index, word) in script.iter().enumerate() {
+        if let Script::Heading(heading) = word {
+            if !heading.trim().is_empty() && from < index {
+                sections.push(from..index);
+                from = index;
+            }
+        }
+    }
+    if from < script.len() {
+        sections.push(from..script.len());
+    }
+    sections
+}
+
+/// Script words that differ from the music words they are paired with
+pub(crate) enum Correction<'stack> {
+    Word {
+        script: &'stack str,
+        music: &'stack music::Word,
+    },
+    Words {
+        script: Vec<&'stack str>,
+        music: Vec<&'stack music::Word>,
+    },
+}
+
+/// Corrections of the music to match the script, with the words paired
+/// up as `read` pairs them
+pub(crate) fn corrections<'stack>(
+    script: &'stack [script::Word],
+    music: &'stack music::Music,
+    mappings: &[script::Mapping],
+    roadmap: &[music::Segment],
+) -> Vec<Correction<'stack>> {
+    align(script, music, mappings, roadmap).into_iter()
+        .flat_map(|builder| builder.corrections)
+        .collect()
+}
+
+fn compare<'stack>(
+    script: &'stack [script::Word],
+    music: &'stack music::Music,
+) -> Builder<'stack> {
+    let words: Vec<&music::Word> = music.phrases.iter()
+        .flat_map(|phrase| phrase.words.iter())
+        .collect();
+    compare_words(script, &words, &music.headings)
+}
+
+fn compare_words<'stack>(
+    script: &'stack [script::Word],
+    music: &[&'stack music::Word],
+    headings: &'stack [music::Heading],
+) -> Builder<'stack> {
+    let music_words = music_words(music);
+    let script_words = script_words(script);
+
+    let mut builder = Builder::new(headings);
     for diff in diff::slice(&script_words, &music_words) {
         match diff {
             Side::Left(Word::Script { word: Script::Heading(text), .. }) =>
                 builder.heading(text.clone()),
 
-            Side::Left(Word::Script { word: Script::Line(text), .. }) =>
-                builder.line(text.clone()),
+            Side::Left(Word::Script { word: Script::Line { number, index }, .. }) =>
+                builder.line(number.clone(), *index),
 
             Side::Both(
                 Word::Script { word: Script::Word(script), .. },
@@ -45,8 +349,7 @@ pub fn read(txt: &str, xml: &str) -> Result<Vec<Section>> {
                 unreachable!("{diff:?}"),
         }
     }
-
-    Ok(builder.build())
+    builder
 }
 
 #[derive(Debug)]
@@ -79,15 +382,13 @@ impl<'stack> std::cmp::PartialEq for Word<'stack> {
     }
 }
 
-fn music_words(part: &music::Music) -> Vec<Word<'_>> {
-    let mut words = Vec::new();
-    for phrase in &part.phrases {
-        for word in &phrase.words {
-            words.push(Word::Music {
-                word,
-                key: key(&word.text),
-            });
-        }
+fn music_words<'stack>(music: &[&'stack music::Word]) -> Vec<Word<'stack>> {
+    let mut words = Vec::with_capacity(music.len());
+    for word in music {
+        words.push(Word::Music {
+            word,
+            key: key(&word.text),
+        });
     }
     words
 }
@@ -102,7 +403,7 @@ fn script_words(script: &[script::Word]) -> Vec<Word<'_>> {
                     key: String::from("@HEADING@"),
                 }),
 
-            script::Word::Line(_) =>
+            script::Word::Line { .. } =>
                 words.push(Word::Script {
                     word,
                     key: String::from("@LINE@"),
@@ -118,7 +419,7 @@ fn script_words(script: &[script::Word]) -> Vec<Word<'_>> {
     words
 }
 
-fn key(text: &str) -> String {
+pub(crate) fn key(text: &str) -> String {
     let mut key = String::with_capacity(text.len());
     for c in text.chars() {
         if c.is_ascii_uppercase() {
@@ -135,27 +436,39 @@ fn key(text: &str) -> String {
 }
 
 struct Builder<'stack> {
+    headings: &'stack [music::Heading],
+    /// Section of the score and bar of the last music word
+    heading: Option<usize>,
+    bar: usize,
     sections: Vec<Section>,
     section: Section,
     line: Line,
     scripts: Vec<&'stack str>,
-    musics: Vec<&'stack str>,
+    musics: Vec<&'stack music::Word>,
+    corrections: Vec<Correction<'stack>>,
 }
 
 impl<'stack> Builder<'stack> {
-    fn new() -> Self {
+    fn new(headings: &'stack [music::Heading]) -> Self {
         Builder {
+            headings,
+            heading: None,
+            bar: 0,
             sections: Vec::new(),
             section: Section {
                 heading: String::new(),
+                music: Vec::new(),
                 lines: Vec::new(),
+                summary: Summary::default(),
             },
             line: Line {
                 number: String::new(),
+                index: None,
                 diffs: Vec::new(),
             },
             scripts: Vec::new(),
             musics: Vec::new(),
+            corrections: Vec::new(),
         }
     }
 
@@ -166,15 +479,20 @@ impl<'stack> Builder<'stack> {
         self.section.heading = heading;
     }
 
-    fn line(&mut self, number: String) {
+    fn line(&mut self, number: String, index: usize) {
         self.flush_diff();
         self.flush_line();
         self.line.number = number;
+        self.line.index = Some(index);
     }
 
     fn same(&mut self, script: &'stack str, music: &'stack music::Word) {
         self.flush_diff();
-        diff_word(&mut self.line.diffs, script, &music.text);
+        self.enter(music);
+        if script != music.text {
+            self.corrections.push(Correction::Word { script, music });
+        }
+        self.word(script, &music.text);
     }
 
     fn script(&mut self, script: &'stack str) {
@@ -182,7 +500,24 @@ impl<'stack> Builder<'stack> {
     }
 
     fn music(&mut self, music: &'stack music::Word) {
-        self.musics.push(&music.text);
+        self.enter(music);
+        self.musics.push(music);
+    }
+
+    /// Note the section of the score a music word is in, as the music
+    /// enters it or the script section starts
+    fn enter(&mut self, music: &music::Word) {
+        let heading = self.headings.iter().rposition(|heading| heading.bar <= music.bar);
+        if let Some(index) = heading {
+            if heading != self.heading || music.bar < self.bar {
+                self.section.music.push(music::Heading {
+                    name: self.headings[index].name.clone(),
+                    bar: music.bar,
+                });
+            }
+        }
+        self.heading = heading;
+        self.bar = music.bar;
     }
 
     fn flush_section(&mut self) {
@@ -191,9 +526,12 @@ impl<'stack> Builder<'stack> {
                 &mut self.section,
                 Section {
                     heading: String::new(),
+                    music: Vec::new(),
                     lines: Vec::new(),
+                    summary: Summary::default(),
                 },
             ));
+            self.heading = None;
         }
     }
 
@@ -203,6 +541,7 @@ impl<'stack> Builder<'stack> {
                 &mut self.line,
                 Line {
                     number: String::new(),
+                    index: None,
                     diffs: Vec::new(),
                 },
             ));
@@ -215,19 +554,22 @@ impl<'stack> Builder<'stack> {
                 (),
 
             ([script], [music]) => {
-                diff_word(&mut self.line.diffs, script, &music);
+                self.corrections.push(Correction::Word { script, music });
+                self.word(script, &music.text);
                 self.scripts.clear();
                 self.musics.clear();
             }
 
             ([], musics) => {
+                self.section.summary.music += musics.len();
                 self.line.diffs.push(Diff::Music(
-                    musics.join(" ")
+                    music_join(musics),
                 ));
                 self.musics.clear();
             }
 
             (scripts, []) => {
+                self.section.summary.script += scripts.len();
                 self.line.diffs.push(Diff::Script(
                     scripts.join(" "),
                 ));
@@ -235,9 +577,18 @@ impl<'stack> Builder<'stack> {
             }
 
             (scripts, musics) => {
+                // words paired up in turn, and the rest left over
+                let summary = &mut self.section.summary;
+                summary.replace += scripts.len().min(musics.len());
+                summary.script += scripts.len().saturating_sub(musics.len());
+                summary.music += musics.len().saturating_sub(scripts.len());
+                self.corrections.push(Correction::Words {
+                    script: scripts.to_vec(),
+                    music: musics.to_vec(),
+                });
                 self.line.diffs.push(Diff::Replace(Replace {
                     script: scripts.join(" "),
-                    music: musics.join(" ")
+                    music: music_join(musics),
                 }));
                 self.scripts.clear();
                 self.musics.clear();
@@ -245,6 +596,22 @@ impl<'stack> Builder<'stack> {
         }
     }
 
+    /// Diff of a script word against the music word it is paired with,
+    /// counted once however many parts of it differ
+    fn word(&mut self, script: &str, music: &str) {
+        let from = self.line.diffs.len();
+        diff_word(&mut self.line.diffs, script, music);
+        let diffs = &self.line.diffs[from..];
+        let summary = &mut self.section.summary;
+        if diffs.iter().all(|diff| matches!(diff, Diff::Same(_))) {
+            summary.same += 1;
+        } else if diffs.iter().all(|diff| matches!(diff, Diff::Same(_) | Diff::Case(_))) {
+            summary.case += 1;
+        } else {
+            summary.replace += 1;
+        }
+    }
+
     fn build(mut self) -> Vec<Section> {
         self.flush_diff();
         self.flush_line();
@@ -253,6 +620,13 @@ impl<'stack> Builder<'stack> {
     }
 }
 
+fn music_join(musics: &[&music::Word]) -> String {
+    let musics: Vec<&str> = musics.iter()
+        .map(|music| music.text.as_str())
+        .collect();
+    musics.join(" ")
+}
+
 fn diff_word(diffs: &mut Vec<Diff>, script: &str, music: &str) {
     if script == music {
         diffs.push(Diff::Same(String::from(script)));
@@ -370,3 +744,128 @@ fn diff_word(diffs: &mut Vec<Diff>, script: &str, music: &str) {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(lyrics: &[(&str, &str)]) -> String {
+        let mut note = String::from("<note><duration>256</duration><voice>1</voice>");
+        for (number, text) in lyrics {
+            note.push_str(&format!(
+                "<lyric number=\"{number}\"><syllabic>single</syllabic><text>{text}</text></lyric>",
+            ));
+        }
+        note.push_str("</note>");
+        note
+    }
+
+    fn texts(section: &Section) -> Vec<String> {
+        section.lines.iter().map(|line| {
+            let diffs: Vec<String> = line.diffs.iter().map(|diff| match diff {
+                Diff::Same(text) => text.clone(),
+                Diff::Music(text) => format!("+{text}"),
+                Diff::Script(text) => format!("-{text}"),
+                Diff::Case(text) => format!("~{text}"),
+                Diff::Replace(replace) => format!("{}/{}", replace.script, replace.music),
+            }).collect();
+            diffs.join(" ")
+        }).collect()
+    }
+
+    #[test]
+    fn test_read_mapped() -> Result<()> {
+        let xml = [
+            "<score-partwise><part id=\"P1\"><measure number=\"1\">",
+            "<barline location=\"left\"><repeat direction=\"forward\"/></barline>",
+            "<direction><direction-type><words>Chorus</words></direction-type></direction>",
+            &note(&[("verse1", "Sing"), ("verse2", "Praise")]),
+            &note(&[("verse1", "high"), ("verse2", "low")]),
+            "<barline location=\"right\"><repeat direction=\"backward\"/></barline>",
+            "</measure><measure number=\"2\">",
+            &note(&[("verse1", "Amen")]),
+            "</measure><measure number=\"3\">",
+            "<direction><direction-type><words>Coda</words></direction-type></direction>",
+            &note(&[("verse1", "Hush")]),
+            "</measure></part></score-partwise>",
+        ].concat();
+
+        // verses out of order, followed by the music after the repeat
+        let txt = "Verse 2\n1. Praise low\nVerse 1\n2. Sing high\n3. Amen Hush\n";
+        let sections = read(txt, &xml)?;
+        let headings: Vec<&str> = sections.iter()
+            .map(|section| section.heading.as_str())
+            .collect();
+        assert_eq!(headings, ["Verse 2", "Verse 1"]);
+        assert_eq!(texts(&sections[0]), ["Praise low"]);
+        assert_eq!(texts(&sections[1]), ["Sing high", "Amen Hush"]);
+        let music: Vec<String> = sections.iter()
+            .flat_map(|section| section.music.iter())
+            .map(|heading| heading.to_string())
+            .collect();
+        assert_eq!(music, ["Chorus (bar 1)", "Chorus (bar 1)", "Coda (bar 3)"]);
+
+        // front matter mapping a heading to bars, and a verse with no music
+        let txt = "---\nCoda: 3\n---\nVerse 1\n1. Sing high\nVerse 2\n2. Praise low Amen\nVerse 3\n3. Hum\nCoda\n4. Hush\n";
+        let sections = read(txt, &xml)?;
+        let headings: Vec<&str> = sections.iter()
+            .map(|section| section.heading.as_str())
+            .collect();
+        assert_eq!(headings, ["Verse 1", "Verse 2", "Verse 3", "Coda"]);
+        assert_eq!(texts(&sections[1]), ["Praise low Amen"]);
+        assert_eq!(texts(&sections[2]), ["-Hum"]);
+        assert_eq!(texts(&sections[3]), ["Hush"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_verse_chorus() -> Result<()> {
+        let xml = [
+            "<score-partwise><part id=\"P1\"><measure number=\"1\">",
+            "<barline location=\"left\"><repeat direction=\"forward\"/></barline>",
+            &note(&[("verse1", "Sing"), ("verse2", "Praise")]),
+            &note(&[("verse1", "high"), ("verse2", "low")]),
+            "<barline location=\"right\"><repeat direction=\"backward\"/></barline>",
+            "</measure><measure number=\"2\">",
+            "<barline location=\"left\"><repeat direction=\"forward\"/></barline>",
+            &note(&[("verse1", "Glory"), ("verse2", "Ever")]),
+            &note(&[("verse1", "be"), ("verse2", "more")]),
+            "<barline location=\"right\"><repeat direction=\"backward\"/></barline>",
+            "</measure></part></score-partwise>",
+        ].concat();
+
+        // the verse headings are passes through the first repeat only
+        let txt = "Verse 1\n1. Sing high\nVerse 2\n2. Praise low\nChorus\n3. Glory be\n4. Ever more\n";
+        let sections = read(txt, &xml)?;
+        let headings: Vec<&str> = sections.iter()
+            .map(|section| section.heading.as_str())
+            .collect();
+        assert_eq!(headings, ["Verse 1", "Verse 2", "Chorus"]);
+        assert_eq!(texts(&sections[0]), ["Sing high"]);
+        assert_eq!(texts(&sections[1]), ["Praise low"]);
+        assert_eq!(texts(&sections[2]), ["Glory be", "Ever more"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_verses() -> Result<()> {
+        let xml = [
+            "<score-partwise><part id=\"P1\"><measure number=\"1\">",
+            &note(&[("verse1", "Sing"), ("verse2", "Praise")]),
+            &note(&[("verse1", "high"), ("verse2", "low")]),
+            "</measure><measure number=\"2\">",
+            &note(&[("verse1", "Amen")]),
+            "</measure></part></score-partwise>",
+        ].concat();
+        let txt = "Song\n\nVerse 1\n1. Sing high Amen\nVerse 2\n1. Praise low Amen\nVerse 3\n1. Amen\n";
+
+        let sections = read_verses(txt, &xml)?;
+        let headings: Vec<&str> = sections.iter()
+            .map(|section| section.heading.as_str())
+            .collect();
+        assert_eq!(headings, ["Verse 1", "Verse 2", "Verse 3"]);
+        assert_eq!(texts(&sections[0]), ["Sing high Amen"]);
+        assert_eq!(texts(&sections[1]), ["Praise low Amen"]);
+        assert_eq!(texts(&sections[2]), ["-Amen"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,302 @@
+use anyhow::Result;
+use ::diff::Result as Side;
+use std::collections::BTreeMap;
+
+use super::{
+    diff::{
+        corrections,
+        Correction,
+    },
+    music::{
+        self,
+        Lyric,
+        Source,
+    },
+    script,
+};
+
+/// MUSICXML with lyrics corrected to match the script
+pub struct Fixed {
+    pub xml: String,
+    pub edits: Vec<Edit>,
+    pub skipped: Vec<Skipped>,
+}
+
+/// Word rewritten in the MUSICXML
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct Edit {
+    pub line: usize,
+    pub music: String,
+    pub script: String,
+}
+
+/// Correction that could not be written into the MUSICXML
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct Skipped {
+    pub line: Option<usize>,
+    pub music: String,
+    pub script: String,
+    pub reason: &'static str,
+}
+
+/// Rewrite the `<lyric><text>` of each single word in the music that is
+/// replaced (or differs in case) in the script, leaving the rest of the
+/// MUSICXML untouched.
+pub fn music(txt: &str, xml: &str) -> Result<Fixed> {
+    let Some(music) = music::read(xml)? else {
+        anyhow::bail!("No lyrics in music")
+    };
+    let script = script::read(txt)?;
+    let mappings = script::front_matter(txt)?;
+    let roadmap = music::roadmap(xml)?;
+
+    let mut builder = Builder {
+        xml,
+        texts: BTreeMap::new(),
+        edits: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for correction in corrections(&script, &music, &mappings, &roadmap) {
+        match correction {
+            Correction::Word { script, music } =>
+                builder.word(script, music),
+
+            Correction::Words { script, music } => {
+                let line = music.first()
+                    .and_then(|word| word.lyrics.first())
+                    .and_then(|lyrics| lyrics.first())
+                    .map(|lyric| builder.line(lyric.source));
+                let music: Vec<&str> = music.iter()
+                    .map(|word| word.text.as_str())
+                    .collect();
+                builder.skipped.push(Skipped {
+                    line,
+                    music: music.join(" "),
+                    script: script.join(" "),
+                    reason: "more than one word",
+                });
+            }
+        }
+    }
+    Ok(builder.build())
+}
+
+struct Builder<'xml> {
+    xml: &'xml str,
+    texts: BTreeMap<Source, String>,
+    edits: Vec<Edit>,
+    skipped: Vec<Skipped>,
+}
+
+impl<'xml> Builder<'xml> {
+    fn line(&self, source: Source) -> usize {
+        1 + self.xml[..source.from].matches('\n').count()
+    }
+
+    fn skip(&mut self, lyrics: &[Lyric], music: &str, script: &str, reason: &'static str) {
+        let line = lyrics.first().map(|lyric| self.line(lyric.source));
+        let skipped = Skipped {
+            line,
+            music: String::from(music),
+            script: String::from(script),
+            reason,
+        };
+        // repeats revisit the same words
+        if !self.skipped.contains(&skipped) {
+            self.skipped.push(skipped);
+        }
+    }
+
+    fn word(&mut self, script: &str, music: &music::Word) {
+        if music.lyrics.is_empty() {
+            self.skip(&[], &music.text, script, "no lyric");
+        }
+        for lyrics in &music.lyrics {
+            let text: String = lyrics.iter()
+                .map(|lyric| lyric.text.as_str())
+                .collect();
+            if text != music.text {
+                self.skip(lyrics, &music.text, script, "lyric shared with other words");
+                continue;
+            }
+            if lyrics.iter().any(|lyric| lyric.text.is_empty()) {
+                self.skip(lyrics, &music.text, script, "empty syllable");
+                continue;
+            }
+            let Some(texts) = split(lyrics, script) else {
+                self.skip(lyrics, &music.text, script, "differing syllable counts");
+                continue;
+            };
+            let conflict = lyrics.iter().zip(&texts).any(|(lyric, text)|
+                matches!(self.texts.get(&lyric.source), Some(other) if other != text)
+            );
+            if conflict {
+                self.skip(lyrics, &music.text, script, "conflicting corrections");
+                continue;
+            }
+            let mut edited = false;
+            for (lyric, text) in lyrics.iter().zip(texts) {
+                if text != lyric.text && !self.texts.contains_key(&lyric.source) {
+                    self.texts.insert(lyric.source, text);
+                    edited = true;
+                }
+            }
+            if edited {
+                self.edits.push(Edit {
+                    line: self.line(lyrics[0].source),
+                    music: music.text.clone(),
+                    script: String::from(script),
+                });
+            }
+        }
+    }
+
+    fn build(self) -> Fixed {
+        let mut xml = String::with_capacity(self.xml.len());
+        let mut at = 0;
+        for (source, text) in self.texts {
+            xml.push_str(&self.xml[at..source.from]);
+            escape(&mut xml, &text);
+            at = source.to;
+        }
+        xml.push_str(&self.xml[at..]);
+        Fixed {
+            xml,
+            edits: self.edits,
+            skipped: self.skipped,
+        }
+    }
+}
+
+/// Split the script word into the syllables of the music word, keeping
+/// each changed letter with the syllable of the letter it replaced.
+fn split(lyrics: &[Lyric], script: &str) -> Option<Vec<String>> {
+    let mut music = String::new();
+    let mut syllables = Vec::new();
+    for (syllable, lyric) in lyrics.iter().enumerate() {
+        music.push_str(&lyric.text);
+        syllables.extend(lyric.text.chars().map(|_| syllable));
+    }
+
+    let mut texts = vec![String::new(); lyrics.len()];
+    let mut at = 0;
+    for diff in ::diff::chars(&music, script) {
+        match diff {
+            Side::Both(_, c) => {
+                texts[syllables[at]].push(c);
+                at += 1;
+            }
+
+            Side::Left(_) =>
+                at += 1,
+
+            Side::Right(c) if at == 0 =>
+                texts[0].push(c),
+
+            Side::Right(c) =>
+                texts[syllables[at - 1]].push(c),
+        }
+    }
+
+    if texts.iter().any(String::is_empty) {
+        None
+    } else {
+        Some(texts)
+    }
+}
+
+fn escape(xml: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => xml.push_str("&amp;"),
+            '<' => xml.push_str("&lt;"),
+            '>' => xml.push_str("&gt;"),
+            c => xml.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lyrics(texts: &[&str]) -> Vec<Lyric> {
+        texts.iter().map(|text| Lyric {
+            source: Source { from: 0, to: 0 },
+            text: String::from(*text),
+        }).collect()
+    }
+
+    /// Text of each `<text>` and the MUSICXML around them
+    fn texts(xml: &str) -> (Vec<&str>, String) {
+        let mut texts = Vec::new();
+        let mut rest = String::new();
+        let mut at = 0;
+        while let Some(start) = xml[at..].find("<text>") {
+            let start = at + start + "<text>".len();
+            let end = start + xml[start..].find("</text>").unwrap();
+            rest.push_str(&xml[at..start]);
+            texts.push(&xml[start..end]);
+            at = end;
+        }
+        rest.push_str(&xml[at..]);
+        (texts, rest)
+    }
+
+    #[test]
+    fn test_music() -> Result<()> {
+        let note = |verse1: (&str, &str), verse2: (&str, &str)| format!(concat!(
+            "<note><duration>256</duration><voice>1</voice>",
+            "<lyric number=\"verse1\"><syllabic>{}</syllabic><text>{}</text></lyric>",
+            "<lyric number=\"verse2\"><syllabic>{}</syllabic><text>{}</text></lyric>",
+            "</note>\n",
+        ), verse1.0, verse1.1, verse2.0, verse2.1);
+        let xml = [
+            "<score-partwise><part id=\"P1\"><measure number=\"1\">\n",
+            "<barline location=\"left\"><repeat direction=\"forward\"/></barline>\n",
+            &note(("single", "Sing"), ("single", "Praise")),
+            &note(("single", "high"), ("single", "low")),
+            "<barline location=\"right\"><repeat direction=\"backward\"/></barline>\n",
+            "</measure><measure number=\"2\">\n",
+            "<barline location=\"left\"><repeat direction=\"forward\"/></barline>\n",
+            &note(("begin", "Glo"), ("begin", "Ev")),
+            &note(("end", "ry"), ("end", "er")),
+            "<barline location=\"right\"><repeat direction=\"backward\"/></barline>\n",
+            "</measure></part></score-partwise>\n",
+        ].concat();
+        let txt = "Verse 2\n1. praise low\nVerse 1\n2. Sing high!\nChorus\n3. glory,\n4. ever\n";
+
+        let fixed = music(txt, &xml)?;
+        let edits: Vec<(usize, &str, &str)> = fixed.edits.iter()
+            .map(|edit| (edit.line, edit.music.as_str(), edit.script.as_str()))
+            .collect();
+        assert_eq!(edits, [(3, "Praise", "praise"), (4, "high", "high!"), (8, "Glory", "glory,"), (8, "Ever", "ever")]);
+        assert_eq!(fixed.skipped, []);
+
+        // only the text of the lyrics changes
+        let (before, rest) = texts(&xml);
+        let (after, fixed_rest) = texts(&fixed.xml);
+        assert_eq!(fixed_rest, rest);
+        assert_eq!(before, ["Sing", "Praise", "high", "low", "Glo", "Ev", "ry", "er"]);
+        assert_eq!(after, ["Sing", "praise", "high!", "low", "glo", "ev", "ry,", "er"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_split() {
+        assert_eq!(
+            split(&lyrics(&["Hel", "lo"]), "hello,"),
+            Some(vec![String::from("hel"), String::from("lo,")]),
+        );
+        assert_eq!(
+            split(&lyrics(&["won", "der", "ful"]), "Wonderful!"),
+            Some(vec![String::from("Won"), String::from("der"), String::from("ful!")]),
+        );
+        assert_eq!(
+            split(&lyrics(&["a", "men"]), "men"),
+            None,
+        );
+    }
+}
@@ -1,18 +1,29 @@
 use askama::Template;
 
+pub mod breaks;
+pub mod check;
 pub mod diff;
+pub mod fix;
 pub mod music;
+pub mod mxl;
 pub mod script;
 
 #[derive(Debug)]
 pub struct Section {
     pub heading: String,
+    /// Sections of the score the music of this section is in, each with
+    /// the bar it enters at
+    pub music: Vec<music::Heading>,
     pub lines: Vec<Line>,
+    /// Count of the words of the lines in each kind of `Diff`
+    pub summary: check::Summary,
 }
 
 #[derive(Debug)]
 pub struct Line {
     pub number: String,
+    /// Line of the script (zero based) the number starts
+    pub index: Option<usize>,
     pub diffs: Vec<Diff>,
 }
 
@@ -51,16 +62,133 @@ fn single_letter(s: &str) -> Option<char> {
     None
 }
 
+/// Lines of a script under a heading, as read by `script::read`
+pub struct ScriptSection {
+    pub heading: String,
+    pub lines: Vec<ScriptLine>,
+}
+
+pub struct ScriptLine {
+    pub number: String,
+    pub words: Vec<String>,
+}
+
+impl ScriptSection {
+    /// Sections of a script, starting a new section at each heading
+    pub fn new(words: Vec<script::Word>) -> Vec<Self> {
+        let mut sections = vec![ScriptSection {
+            heading: String::new(),
+            lines: Vec::new(),
+        }];
+        for word in words {
+            let section = sections.last_mut().unwrap();
+            match word {
+                script::Word::Heading(heading) =>
+                    sections.push(ScriptSection {
+                        heading,
+                        lines: Vec::new(),
+                    }),
+
+                script::Word::Line { number, .. } =>
+                    section.lines.push(ScriptLine {
+                        number,
+                        words: Vec::new(),
+                    }),
+
+                script::Word::Word(text) =>
+                    if let Some(line) = section.lines.last_mut() {
+                        line.words.push(text);
+                    },
+            }
+        }
+        sections.retain(|section|
+            !section.heading.trim().is_empty() || !section.lines.is_empty()
+        );
+        sections
+    }
+}
+
+/// Phrases of music sung in the same pass through a repeat
+pub struct MusicSection {
+    pub heading: String,
+    pub phrases: Vec<MusicPhrase>,
+}
+
+pub struct MusicPhrase {
+    pub number: usize,
+    pub words: Vec<MusicWord>,
+}
+
+pub struct MusicWord {
+    pub bar: usize,
+    pub text: String,
+}
+
+impl MusicSection {
+    /// Sections of music, starting a new section when the verse of the
+    /// first word of a phrase changes
+    pub fn new(phrases: Vec<music::Phrase>) -> Vec<Self> {
+        let mut sections: Vec<MusicSection> = Vec::new();
+        let mut verse = None;
+        for (number, phrase) in phrases.into_iter().enumerate() {
+            let next = phrase.words.first().and_then(|word| word.verse);
+            if sections.is_empty() || next != verse {
+                sections.push(MusicSection {
+                    heading: match next {
+                        Some(next) => format!("Verse {}", next + 1),
+                        None => String::new(),
+                    },
+                    phrases: Vec::new(),
+                });
+                verse = next;
+            }
+            let words = phrase.words.into_iter()
+                .map(|word| MusicWord {
+                    bar: word.bar,
+                    text: word.text,
+                })
+                .collect();
+            sections.last_mut().unwrap().phrases.push(MusicPhrase {
+                number: number + 1,
+                words,
+            });
+        }
+        sections
+    }
+}
+
+/// Music of a `Diff::Case`, which differs from the script only in case
+fn other_case(script: &str) -> String {
+    script.chars()
+        .map(|c| if c.is_uppercase() {
+            c.to_ascii_lowercase()
+        } else {
+            c.to_ascii_uppercase()
+        })
+        .collect()
+}
+
 pub struct Link {
     pub selected: bool,
     pub href: String,
     pub title: String,
 }
 
+/// Row of the dashboard, summarising the diff of a song
+pub struct Song {
+    pub href: String,
+    pub title: String,
+    pub summary: Option<check::Summary>,
+    pub error: Option<String>,
+}
+
 #[derive(askama::Template)]
 #[template(path = "home.html")]
 pub struct HomePage {
     pub error: Option<String>,
+    /// Links to sort the songs by each column
+    pub sorts: Vec<Link>,
+    pub songs: Vec<Song>,
     pub folders: Vec<Link>,
     pub scripts: Vec<Link>,
     pub musics: Vec<Link>,
@@ -71,6 +199,8 @@ pub struct HomePage {
 pub struct FolderPage {
     pub error: Option<String>,
     pub folder: String,
+    pub breadcrumbs: Vec<Link>,
+    pub folders: Vec<Link>,
     pub scripts: Vec<Link>,
     pub musics: Vec<Link>,
 }
@@ -80,7 +210,73 @@ pub struct FolderPage {
 pub struct DiffPage {
     pub error: Option<String>,
     pub folder: String,
+    pub breadcrumbs: Vec<Link>,
     pub sections: Vec<Section>,
+    /// Script and music in two columns rather than inline
+    pub side_by_side: bool,
+    /// Links to switch between the inline and side by side layouts
+    pub layouts: Vec<Link>,
+    /// Views of the script and music on their own
+    pub views: Vec<Link>,
+    /// Lines of the script to edit in place, when it can be saved
+    pub edit: Option<EditScript>,
+    /// Server sent events of changes to the script or music
+    pub events: Option<String>,
+}
+
+/// Script behind a diff page, where edits to its lines are posted
+pub struct EditScript {
+    pub folder: String,
+    pub script: String,
+    pub music: String,
+    /// `script::version` of the TXT the page was made from
+    pub version: String,
+    pub lines: Vec<String>,
+}
+
+impl EditScript {
+    pub fn new(folder: &str, script: &str, music: &str, txt: &str) -> Self {
+        EditScript {
+            folder: String::from(folder),
+            script: String::from(script),
+            music: String::from(music),
+            version: script::version(txt),
+            lines: txt.lines().map(String::from).collect(),
+        }
+    }
+
+    pub fn line(&self, index: &usize) -> &str {
+        self.lines.get(*index).map_or("", String::as_str)
+    }
+}
+
+#[derive(askama::Template)]
+#[template(path = "script.html")]
+pub struct ScriptPage {
+    pub error: Option<String>,
+    pub title: String,
+    pub breadcrumbs: Vec<Link>,
+    pub sections: Vec<ScriptSection>,
+}
+
+#[derive(askama::Template)]
+#[template(path = "music.html")]
+pub struct MusicPage {
+    pub error: Option<String>,
+    pub title: String,
+    pub breadcrumbs: Vec<Link>,
+    /// Bars in the order they are played, like `1-8, 9-16 (v1), ...`
+    pub roadmap: String,
+    pub sections: Vec<MusicSection>,
+}
+
+#[derive(askama::Template)]
+#[template(path = "upload.html")]
+pub struct UploadPage {
+    pub error: Option<String>,
+    pub breadcrumbs: Vec<Link>,
+    /// Largest file accepted, in mebibytes
+    pub limit: u64,
 }
 
 #[derive(askama::Template)]
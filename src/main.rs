@@ -1,114 +1,340 @@
 use anyhow::Context;
 use askama::Template;
+use clap::Parser;
 use rocket::{
-    config::{
-        Config,
-        LogLevel,
+    config::Config,
+    data::{
+        Limits,
+        ToByteUnit,
     },
+    fairing::AdHoc,
+    form::{
+        self,
+        Form,
+        FromForm,
+    },
+    fs::TempFile,
     get,
+    http::{
+        Cookie,
+        CookieJar,
+        RawStr,
+        Status,
+    },
     launch,
-    response::content::RawHtml,
+    post,
+    response::{
+        content::RawHtml,
+        Redirect,
+        stream::{
+            Event,
+            EventStream,
+        },
+    },
     routes,
+    tokio::{
+        io::AsyncReadExt,
+        select,
+        sync::broadcast::error::RecvError,
+    },
+    Shutdown,
     State,
 };
 use std::path::{Path, PathBuf};
-use std::thread;
-use std::time::Duration;
-use webbrowser;
 
 use lyric_check::{
+    check::Folder,
     DiffPage,
+    EditScript,
     ErrorPage,
     FolderPage,
     HomePage,
     Link,
+    MusicPage,
+    MusicSection,
+    ScriptPage,
+    ScriptSection,
+    Song,
+    UploadPage,
 };
 
 mod asset;
+mod dashboard;
+mod options;
+mod root;
+mod watch;
 
 use asset::Asset;
+use dashboard::Dashboard;
+use options::Options;
+use root::{
+    Refused,
+    Root,
+};
+use watch::Watch;
+
+#[get("/?<sort>")]
+async fn html_root(
+    root: &State<Root>,
+    dashboard: &State<Dashboard>,
+    sort: Option<&str>,
+) -> (Status, RawHtml<String>) {
+    html(page_root(root, dashboard, None, None, sort).await)
+}
 
-#[get("/")]
-async fn html_root(root: &State<PathBuf>) -> RawHtml<String> {
-    match page_root(&root).await {
-        Ok(html) =>
-            RawHtml(html),
+#[get("/script/<script>")]
+async fn html_root_script(
+    root: &State<Root>,
+    dashboard: &State<Dashboard>,
+    script: String,
+) -> (Status, RawHtml<String>) {
+    html(page_root(root, dashboard, Some(script), None, None).await)
+}
 
-        Err(error) =>
-            RawHtml(ErrorPage::anyhow(error)),
-    }
+#[get("/script/<script>/music/<music>")]
+async fn html_root_script_music(
+    root: &State<Root>,
+    dashboard: &State<Dashboard>,
+    script: String,
+    music: String,
+) -> (Status, RawHtml<String>) {
+    html(page_root(root, dashboard, Some(script), Some(music), None).await)
 }
 
-#[get("/folder/<folder>")]
-async fn html_folder(root: &State<PathBuf>, folder: &str) -> RawHtml<String> {
-    match page_folder(&root, folder, None, None).await {
-        Ok(html) =>
-            RawHtml(html),
+#[get("/script/<script>/view")]
+async fn html_root_script_view(
+    root: &State<Root>,
+    script: &str,
+) -> (Status, RawHtml<String>) {
+    html(page_script_view(root, "", script).await)
+}
 
-        Err(error) =>
-            RawHtml(ErrorPage::anyhow(error)),
+#[get("/music/<music>")]
+async fn html_root_music(
+    root: &State<Root>,
+    dashboard: &State<Dashboard>,
+    music: String,
+) -> (Status, RawHtml<String>) {
+    html(page_root(root, dashboard, None, Some(music), None).await)
+}
+
+#[get("/music/<music>/view")]
+async fn html_root_music_view(
+    root: &State<Root>,
+    music: &str,
+) -> (Status, RawHtml<String>) {
+    html(page_music_view(root, "", music).await)
+}
+
+#[get("/script/<script>/music/<music>/diff?<layout>")]
+async fn html_root_diff(
+    root: &State<Root>,
+    cookies: &CookieJar<'_>,
+    script: &str,
+    music: &str,
+    layout: Option<&str>,
+) -> (Status, RawHtml<String>) {
+    let side_by_side = side_by_side(cookies, layout);
+    html(page_folder_diff(root, "", script, music, side_by_side).await)
+}
+
+#[get("/folder/<path..>?<layout>")]
+async fn html_folder(
+    root: &State<Root>,
+    cookies: &CookieJar<'_>,
+    path: PathBuf,
+    layout: Option<&str>,
+) -> (Status, RawHtml<String>) {
+    let side_by_side = side_by_side(cookies, layout);
+    html(page_path(root, &path, side_by_side).await)
+}
+
+const LAYOUT: &str = "layout";
+const INLINE: &str = "inline";
+const SIDE_BY_SIDE: &str = "side-by-side";
+
+/// Whether to lay out diffs side by side, as chosen by the query and
+/// remembered in a cookie for later pages
+fn side_by_side(cookies: &CookieJar<'_>, layout: Option<&str>) -> bool {
+    match layout {
+        Some(layout @ (INLINE | SIDE_BY_SIDE)) => {
+            let cookie = Cookie::build((LAYOUT, String::from(layout)))
+                .path("/")
+                .permanent();
+            cookies.add(cookie);
+            layout == SIDE_BY_SIDE
+        }
+
+        _ =>
+            cookies.get(LAYOUT).is_some_and(|cookie| cookie.value() == SIDE_BY_SIDE),
     }
 }
 
-#[get("/folder/<folder>/script/<script>")]
-async fn html_folder_script(
-    root: &State<PathBuf>,
-    folder: &str, 
-    script: String,
-) -> RawHtml<String> {
-    match page_folder(&root, folder, Some(script), None).await {
+/// Script and music to diff without writing them into the root
+#[derive(FromForm)]
+struct Upload<'r> {
+    script: TempFile<'r>,
+    music: TempFile<'r>,
+}
+
+/// Largest upload accepted, in mebibytes
+struct UploadLimit(u64);
+
+#[get("/upload")]
+fn html_upload(limit: &State<UploadLimit>) -> RawHtml<String> {
+    RawHtml(page_upload(limit.0, None))
+}
+
+#[post("/upload", data = "<upload>")]
+async fn html_upload_diff(
+    limit: &State<UploadLimit>,
+    cookies: &CookieJar<'_>,
+    upload: Result<Form<Upload<'_>>, form::Errors<'_>>,
+) -> (Status, RawHtml<String>) {
+    let upload = match upload {
+        Ok(upload) => upload.into_inner(),
+        Err(errors) => {
+            let error = errors.iter()
+                .map(|error| match error.name.as_ref() {
+                    Some(name) => format!("{name}: {error}"),
+                    None => error.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            return (Status::BadRequest, RawHtml(page_upload(limit.0, Some(error))));
+        }
+    };
+    let side_by_side = side_by_side(cookies, None);
+    match page_upload_diff(upload, side_by_side).await {
         Ok(html) =>
-            RawHtml(html),
+            (Status::Ok, RawHtml(html)),
 
         Err(error) =>
-            RawHtml(ErrorPage::anyhow(error)),
+            (Status::UnprocessableEntity, RawHtml(page_upload(limit.0, Some(format!("{error:#}"))))),
     }
 }
 
-#[get("/folder/<folder>/script/<script>/music/<music>")]
-async fn html_folder_script_music(
-    root: &State<PathBuf>,
-    folder: &str, 
-    script: String,
-    music: String,
-) -> RawHtml<String> {
-    match page_folder(&root, folder, Some(script), Some(music)).await {
-        Ok(html) =>
-            RawHtml(html),
+/// Line of a script edited on its diff page
+#[derive(FromForm)]
+struct Edit<'r> {
+    folder: &'r str,
+    script: &'r str,
+    music: &'r str,
+    /// `script::version` of the TXT the line was read from
+    version: &'r str,
+    line: usize,
+    text: &'r str,
+}
 
-        Err(error) =>
-            RawHtml(ErrorPage::anyhow(error)),
+#[post("/edit", data = "<edit>")]
+async fn html_edit(
+    root: &State<Root>,
+    cookies: &CookieJar<'_>,
+    edit: Form<Edit<'_>>,
+) -> Result<Redirect, (Status, RawHtml<String>)> {
+    let side_by_side = side_by_side(cookies, None);
+    match save_edit(root, &edit).await {
+        Ok(()) =>
+            Ok(Redirect::to(format!("{}/script/{}/music/{}/diff#line-{}",
+                folder_href(edit.folder),
+                encode(edit.script),
+                encode(edit.music),
+                edit.line,
+            ))),
+
+        Err(error) => {
+            let status = match error.downcast_ref::<Refused>() {
+                Some(Refused::NotFound(_)) => Status::NotFound,
+                Some(Refused::Forbidden(_)) => Status::Forbidden,
+                None => Status::Conflict,
+            };
+            let page = diff_page(root, edit.folder, edit.script, edit.music, side_by_side).await;
+            let html = match page {
+                Ok(mut page) => {
+                    page.error = Some(format!("{error:#}"));
+                    page.render().unwrap()
+                }
+                Err(_) =>
+                    ErrorPage::anyhow(error),
+            };
+            Err((status, RawHtml(html)))
+        }
     }
 }
 
-#[get("/folder/<folder>/music/<music>")]
-async fn html_folder_music(
-    root: &State<PathBuf>,
-    folder: &str, 
-    music: String,
-) -> RawHtml<String> {
-    match page_folder(&root, folder, None, Some(music)).await {
-        Ok(html) =>
-            RawHtml(html),
+/// Write the edited line into the script, unless it changed since the
+/// line was read, replacing the whole file at once
+async fn save_edit(root: &Root, edit: &Edit<'_>) -> anyhow::Result<()> {
+    let path = root.resolve(&Path::new(edit.folder).join(format!("{}.txt", edit.script)))?;
+    let txt = tokio::fs::read_to_string(&path).await
+        .with_context(|| format!("Read from {}", path.display()))?;
+    if lyric_check::script::version(&txt) != edit.version {
+        anyhow::bail!("{} changed since it was read, so line {} was not saved",
+            path.display(),
+            edit.line + 1,
+        );
+    }
+    let txt = lyric_check::script::edit(&txt, edit.line, edit.text)?;
 
-        Err(error) =>
-            RawHtml(ErrorPage::anyhow(error)),
+    let Some(name) = path.file_name() else {
+        anyhow::bail!("No file name in {}", path.display());
+    };
+    let temp = path.with_file_name(format!(".{}.edit", name.to_string_lossy()));
+    tokio::fs::write(&temp, txt).await
+        .with_context(|| format!("Write to {}", temp.display()))?;
+    if let Err(error) = tokio::fs::rename(&temp, &path).await {
+        let _ = tokio::fs::remove_file(&temp).await;
+        return Err(error).with_context(|| format!("Write to {}", path.display()));
     }
+    Ok(())
 }
 
-#[get("/folder/<folder>/script/<script>/music/<music>/diff")]
-async fn html_folder_diff(
-    root: &State<PathBuf>,
-    folder: &str,
-    script: &str,
-    music: &str,
-) -> RawHtml<String> {
-    match page_folder_diff(&root, folder, script, music).await {
+/// Page, or error page with a status for refused paths
+fn html(page: anyhow::Result<String>) -> (Status, RawHtml<String>) {
+    match page {
         Ok(html) =>
-            RawHtml(html),
+            (Status::Ok, RawHtml(html)),
+
+        Err(error) => {
+            let status = match error.downcast_ref::<Refused>() {
+                Some(Refused::NotFound(_)) => Status::NotFound,
+                Some(Refused::Forbidden(_)) => Status::Forbidden,
+                None => Status::Ok,
+            };
+            (status, RawHtml(ErrorPage::anyhow(error)))
+        }
+    }
+}
 
-        Err(error) =>
-            RawHtml(ErrorPage::anyhow(error)),
+#[get("/events?<path>")]
+fn events(
+    root: &State<Root>,
+    watch: &State<Watch>,
+    path: Vec<String>,
+    mut shutdown: Shutdown,
+) -> EventStream![] {
+    let paths: Vec<PathBuf> = path.iter().map(|path| root.path().join(path)).collect();
+    let mut changes = watch.subscribe();
+    EventStream! {
+        loop {
+            let changed = select! {
+                changed = changes.recv() => changed,
+                _ = &mut shutdown => break,
+            };
+            match changed {
+                Ok(changed) if paths.contains(&changed) =>
+                    yield Event::data("change"),
+
+                Ok(_) =>
+                    (),
+
+                Err(RecvError::Lagged(_)) =>
+                    yield Event::data("change"),
+
+                Err(RecvError::Closed) =>
+                    break,
+            }
+        }
     }
 }
 
@@ -135,39 +361,71 @@ fn favicon32() -> Option<Asset> {
 #[launch]
 async fn rocket() -> _ {
     yansi::disable();
-    
-    let mut root = std::env::current_dir().unwrap();
-    if let Some(arg) = std::env::args().skip(1).next() {
-        let arg = Path::new(&arg);
-        if let Ok(meta) = std::fs::metadata(arg) {
-            if meta.is_dir() {
-                root = PathBuf::from(arg);
-            }
-        }
-    }
 
-    thread::spawn(move || {
-        thread::sleep(Duration::from_secs(1));
-        webbrowser::open("http://localhost:7000").unwrap();
-    });
+    let options = Options::parse();
+    let root = match options.root.clone() {
+        Some(root) => root,
+        None => std::env::current_dir().unwrap(),
+    };
+    let root = match Root::new(&root) {
+        Ok(root) => root,
+        Err(error) => {
+            eprintln!("Root folder {}: {error}", root.display());
+            std::process::exit(1);
+        }
+    };
 
     let mut config = Config::release_default();
-    config.log_level = LogLevel::Normal;
+    config.log_level = options.log.into();
     #[cfg(not(debug_assertions))] {
         config.cli_colors = false;
     }
-    // config.address = "localhost";
-    config.port = 7000;
+    config.limits = Limits::default()
+        .limit("file", options.upload_limit.mebibytes())
+        .limit("data-form", (2 * options.upload_limit + 1).mebibytes());
+    config.address = options.address;
+    config.port = match options.free_port() {
+        Some(port) => port,
+        None => {
+            eprintln!("No free port from {} on {}", options.port, options.address);
+            std::process::exit(1);
+        }
+    };
+    if config.port != options.port {
+        println!("Port {} is busy, using {}", options.port, config.port);
+    }
+
+    let browser = !options.no_browser;
+    let launched = AdHoc::on_liftoff("Browser", move |rocket| Box::pin(async move {
+        let url = options::url(rocket.config().address, rocket.config().port);
+        println!("Lyric Check at {url}");
+        if browser {
+            if let Err(error) = webbrowser::open(&url) {
+                eprintln!("Open a web browser at {url} ({error})");
+            }
+        }
+    }));
 
     rocket::custom(config)
+        .attach(launched)
+        .attach(Watch::fairing(root.path().to_path_buf()))
         .manage(root)
+        .manage(UploadLimit(options.upload_limit))
+        .manage(Dashboard::new())
+        .manage(Watch::new())
         .mount("/", routes![
             html_root,
+            html_root_script,
+            html_root_script_music,
+            html_root_script_view,
+            html_root_music,
+            html_root_music_view,
+            html_root_diff,
             html_folder,
-            html_folder_script,
-            html_folder_script_music,
-            html_folder_music,
-            html_folder_diff,
+            html_upload,
+            html_upload_diff,
+            html_edit,
+            events,
             index_css,
             favicon,
             favicon16,
@@ -175,187 +433,512 @@ async fn rocket() -> _ {
         ])
 }
 
-struct Home {
-    folders: Vec<String>,
-    scripts: Vec<String>,
-    musics: Vec<String>,
+/// Folders, scripts and musics of a folder within the root
+async fn read_folder(root: &Root, folder: &str) -> anyhow::Result<Folder> {
+    let path = root.resolve(Path::new(folder))?;
+    Ok(tokio::task::spawn_blocking(move || Folder::read(&path)).await??)
 }
 
-impl Home {
-    async fn read(root: &Path) -> anyhow::Result<Self> {
-        let mut answer = Home {
-            folders: Vec::new(),
-            scripts: Vec::new(),
-            musics: Vec::new(),
-        };
+/// Folder (empty for the root) and selection within it of a path
+/// `/folder/<path..>`, where the folder is followed by any of
+/// `script/<script>`, `music/<music>` and `diff`, or by one of
+/// `script/<script>/view` and `music/<music>/view`
+#[derive(Debug)]
+#[derive(PartialEq)]
+enum Location {
+    Folder(String),
+    Select {
+        folder: String,
+        script: Option<String>,
+        music: Option<String>,
+    },
+    Diff {
+        folder: String,
+        script: String,
+        music: String,
+    },
+    Script {
+        folder: String,
+        script: String,
+    },
+    Music {
+        folder: String,
+        music: String,
+    },
+}
 
-        let mut dir = tokio::fs::read_dir(root).await?;
-        while let Some(entry) = dir.next_entry().await? {
-            if let Some(name) = entry.file_name().to_str() {
-                let file_type = entry.file_type().await?;
-                if file_type.is_dir() {
-                    answer.folders.push(String::from(name));
-                } else if file_type.is_file() {
-                    if let Some(name) = name.strip_suffix(".txt") {
-                        answer.scripts.push(String::from(name));
-                    }
-                    if let Some(name) = name.strip_suffix(".musicxml") {
-                        answer.musics.push(String::from(name));
-                    }
-                }
-            }
+impl Location {
+    fn new(root: &Root, path: &Path) -> Self {
+        let segments: Vec<String> = path.iter()
+            .map(|segment| segment.to_string_lossy().into_owned())
+            .collect();
+        let folder = |segments: &[String]| segments.join("/");
+        // a folder named `script` or `music` wins
+        if root.resolve(path).is_ok_and(|path| path.is_dir()) {
+            return Location::Folder(folder(&segments));
         }
+        match &segments[..] {
+            [parent @ .., s, script, m, music, diff]
+            if s == "script" && m == "music" && diff == "diff" =>
+                Location::Diff {
+                    folder: folder(parent),
+                    script: script.clone(),
+                    music: music.clone(),
+                },
+
+            [parent @ .., s, script, view] if s == "script" && view == "view" =>
+                Location::Script {
+                    folder: folder(parent),
+                    script: script.clone(),
+                },
 
-        answer.folders.sort();
-        answer.scripts.sort();
-        answer.musics.sort();
+            [parent @ .., m, music, view] if m == "music" && view == "view" =>
+                Location::Music {
+                    folder: folder(parent),
+                    music: music.clone(),
+                },
+
+            [parent @ .., s, script, m, music]
+            if s == "script" && m == "music" =>
+                Location::Select {
+                    folder: folder(parent),
+                    script: Some(script.clone()),
+                    music: Some(music.clone()),
+                },
+
+            [parent @ .., s, script] if s == "script" =>
+                Location::Select {
+                    folder: folder(parent),
+                    script: Some(script.clone()),
+                    music: None,
+                },
+
+            [parent @ .., m, music] if m == "music" =>
+                Location::Select {
+                    folder: folder(parent),
+                    script: None,
+                    music: Some(music.clone()),
+                },
 
-        Ok(answer)
+            _ =>
+                Location::Folder(folder(&segments)),
+        }
     }
 }
 
-struct Folder {
-    scripts: Vec<String>,
-    musics: Vec<String>,
-}
+async fn page_path(
+    root: &Root,
+    path: &Path,
+    side_by_side: bool,
+) -> anyhow::Result<String> {
+    match Location::new(root, path) {
+        Location::Folder(folder) =>
+            page_folder(root, &folder, None, None, side_by_side).await,
 
-impl Folder {
-    async fn read(root: &Path, folder: &str) -> anyhow::Result<Self> {
-        let mut answer = Folder {
-            scripts: Vec::new(),
-            musics: Vec::new(),
-        };
+        Location::Select { folder, script, music } =>
+            page_folder(root, &folder, script, music, side_by_side).await,
 
-        let root = root.join(folder);
-        let mut dir = tokio::fs::read_dir(root).await?;
-        while let Some(entry) = dir.next_entry().await? {
-            if let Some(name) = entry.file_name().to_str() {
-                let file_type = entry.file_type().await?;
-                if file_type.is_file() {
-                    if let Some(name) = name.strip_suffix(".txt") {
-                        answer.scripts.push(String::from(name));
-                    }
-                    if let Some(name) = name.strip_suffix(".musicxml") {
-                        answer.musics.push(String::from(name));
-                    }
-                }
-            }
-        }
+        Location::Diff { folder, script, music } =>
+            page_folder_diff(root, &folder, &script, &music, side_by_side).await,
 
-        answer.scripts.sort();
-        answer.musics.sort();
+        Location::Script { folder, script } =>
+            page_script_view(root, &folder, &script).await,
+
+        Location::Music { folder, music } =>
+            page_music_view(root, &folder, &music).await,
+    }
+}
 
-        Ok(answer)
+/// Links to the root and each folder leading to `folder`
+fn breadcrumbs(folder: &str) -> Vec<Link> {
+    let mut links = vec![Link {
+        selected: false,
+        href: String::from("/"),
+        title: String::from("Lyric Check"),
+    }];
+    let mut href = String::from("/folder");
+    for name in folder.split('/').filter(|name| !name.is_empty()) {
+        href.push('/');
+        href.push_str(&encode(name));
+        links.push(Link {
+            selected: false,
+            href: href.clone(),
+            title: String::from(name),
+        });
     }
+    links
 }
 
-async fn page_root(root: &Path) -> anyhow::Result<String> {
-    let dir = Home::read(root).await?;
+/// Prefix of the links within a folder (empty for the root)
+fn folder_href(folder: &str) -> String {
+    if folder.is_empty() {
+        String::new()
+    } else {
+        format!("/folder/{}", encode_segments(folder))
+    }
+}
+
+/// Percent encode a name for a segment of a path or a value of a query
+fn encode(name: &str) -> String {
+    RawStr::new(name).percent_encode().to_string()
+}
+
+/// Percent encode each segment of a `/` separated path
+fn encode_segments(path: &str) -> String {
+    path.split('/')
+        .map(encode)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn folder_links(folder: &str, folders: Vec<String>) -> Vec<Link> {
+    folders.into_iter().map(|name|
+        Link {
+            selected: false,
+            href: if folder.is_empty() {
+                format!("/folder/{}", encode(&name))
+            } else {
+                format!("{}/{}", folder_href(folder), encode(&name))
+            },
+            title: name,
+        }
+    ).collect()
+}
+
+async fn page_root(
+    root: &Root,
+    dashboard: &Dashboard,
+    selected_script: Option<String>,
+    selected_music: Option<String>,
+    sort: Option<&str>,
+) -> anyhow::Result<String> {
+    let sort = sort.unwrap_or("song");
+    let mut rows = dashboard.rows(root.path()).await;
+    dashboard::sort(&mut rows, sort);
+    let sorts = dashboard::SORTS.iter().map(|(name, title)|
+        Link {
+            selected: *name == sort,
+            href: format!("/?sort={name}"),
+            title: String::from(*title),
+        }
+    ).collect();
+    let songs = rows.into_iter().map(|row| {
+        let mut title = if row.folder.is_empty() {
+            row.script.clone()
+        } else {
+            format!("{}/{}", row.folder, row.script)
+        };
+        if row.music != row.script {
+            title = format!("{title} ({})", row.music);
+        }
+        let (summary, error) = match row.summary {
+            Ok(summary) => (Some(summary), None),
+            Err(error) => (None, Some(error)),
+        };
+        Song {
+            href: format!("{}/script/{}/music/{}/diff",
+                folder_href(&row.folder),
+                encode(&row.script),
+                encode(&row.music),
+            ),
+            title,
+            summary,
+            error,
+        }
+    }).collect();
+
+    let dir = read_folder(root, "").await?;
+    let (scripts, musics) = select(
+        "",
+        dir.scripts,
+        dir.musics,
+        selected_script,
+        selected_music,
+    );
     let page = HomePage {
         error: None,
-        folders: dir.folders.into_iter().map(|folder|
-            Link {
-                selected: false,
-                href: format!("/folder/{folder}"),
-                title: folder,
-            }
-        ).collect(),
-        scripts: dir.scripts.into_iter().map(|script|
-            Link {
-                selected: false,
-                href: format!("/script/{script}"),
-                title: script,
-            }
-        ).collect(),
-        musics: dir.musics.into_iter().map(|music|
-            Link {
-                selected: false,
-                href: format!("/music/{music}"),
-                title: music,
-            }
-        ).collect(),
+        sorts,
+        songs,
+        folders: folder_links("", dir.folders),
+        scripts,
+        musics,
     };
     Ok(page.render().unwrap())
 }
 
 async fn page_folder(
-    root: &Path,
+    root: &Root,
     folder: &str,
-    mut selected_script: Option<String>,
-    mut selected_music: Option<String>,
+    selected_script: Option<String>,
+    selected_music: Option<String>,
+    side_by_side: bool,
 ) -> anyhow::Result<String> {
-    let dir = Folder::read(root, folder).await?;
+    let dir = read_folder(root, folder).await?;
     if let ([script], [music]) = (&dir.scripts[..], &dir.musics[..]) {
-        if let Ok(html) = page_folder_diff(root, folder, script, music).await {
+        if let Ok(html) = page_folder_diff(root, folder, script, music, side_by_side).await {
             return Ok(html);
         }
     }
-    if let [script] = &dir.scripts[..] {
-        selected_script = Some(script.clone());
-    }
-    if let [music] = &dir.musics[..] {
-        selected_music = Some(music.clone());
-    }
+    let (scripts, musics) = select(
+        folder,
+        dir.scripts,
+        dir.musics,
+        selected_script,
+        selected_music,
+    );
     let page = FolderPage {
         error: None,
         folder: String::from(folder),
-        scripts: dir.scripts.into_iter().map(|script|
-            Link {
-                selected: if let Some(ref selected) = selected_script {
-                    selected == &script
-                } else {
-                    false
-                },
-                href: if let Some(ref music) = selected_music {
-                    format!("/folder/{folder}/script/{script}/music/{music}/diff")
-                } else {
-                    format!("/folder/{folder}/script/{script}")
-                },
-                title: script,
-            }
-        ).collect(),
-        musics: dir.musics.into_iter().map(|music|
-            Link {
-                selected: if let Some(ref selected) = selected_music {
-                    selected == &music
-                } else {
-                    false
-                },
-                href: if let Some(ref script) = selected_script {
-                    format!("/folder/{folder}/script/{script}/music/{music}/diff")
-                } else {
-                    format!("/folder/{folder}/music/{music}")
-                },
-                title: music,
-            }
-        ).collect(),
+        breadcrumbs: breadcrumbs(folder),
+        folders: folder_links(folder, dir.folders),
+        scripts,
+        musics,
     };
     Ok(page.render().unwrap())
 }
 
+/// Links to select a script and music in a folder (empty for the root),
+/// leading to their diff once both are selected.
+fn select(
+    folder: &str,
+    scripts: Vec<String>,
+    musics: Vec<String>,
+    mut selected_script: Option<String>,
+    mut selected_music: Option<String>,
+) -> (Vec<Link>, Vec<Link>) {
+    if let [script] = &scripts[..] {
+        selected_script = Some(script.clone());
+    }
+    if let [music] = &musics[..] {
+        selected_music = Some(music.clone());
+    }
+    let folder = folder_href(folder);
+    let scripts = scripts.into_iter().map(|script|
+        Link {
+            selected: if let Some(ref selected) = selected_script {
+                selected == &script
+            } else {
+                false
+            },
+            href: if let Some(ref music) = selected_music {
+                format!("{folder}/script/{}/music/{}/diff", encode(&script), encode(music))
+            } else {
+                format!("{folder}/script/{}", encode(&script))
+            },
+            title: script,
+        }
+    ).collect();
+    let musics = musics.into_iter().map(|music|
+        Link {
+            selected: if let Some(ref selected) = selected_music {
+                selected == &music
+            } else {
+                false
+            },
+            href: if let Some(ref script) = selected_script {
+                format!("{folder}/script/{}/music/{}/diff", encode(script), encode(&music))
+            } else {
+                format!("{folder}/music/{}", encode(&music))
+            },
+            title: music,
+        }
+    ).collect();
+    (scripts, musics)
+}
+
 async fn page_folder_diff(
-    root: &Path,
-    folder_str: &str,
+    root: &Root,
+    folder: &str,
     script: &str,
     music: &str,
+    side_by_side: bool,
 ) -> anyhow::Result<String> {
-    let folder = root.join(folder_str);
+    let page = diff_page(root, folder, script, music, side_by_side).await?;
+    Ok(page.render().unwrap())
+}
+
+async fn diff_page(
+    root: &Root,
+    folder_str: &str,
+    script_name: &str,
+    music_name: &str,
+    side_by_side: bool,
+) -> anyhow::Result<DiffPage> {
+    let folder = Path::new(folder_str);
+    let (script, music) = (script_name, music_name);
+
+    let title = if folder_str.is_empty() {
+        String::from(script)
+    } else {
+        String::from(folder_str)
+    };
+
+    let href = folder_href(folder_str);
+    let views = vec![
+        Link {
+            selected: false,
+            href: format!("{href}/script/{}/view", encode(script)),
+            title: String::from("Script"),
+        },
+        Link {
+            selected: false,
+            href: format!("{href}/music/{}/view", encode(music)),
+            title: String::from("Music"),
+        },
+    ];
+
+    let layouts = vec![
+        Link {
+            selected: !side_by_side,
+            href: format!("?{LAYOUT}={INLINE}"),
+            title: String::from("Inline"),
+        },
+        Link {
+            selected: side_by_side,
+            href: format!("?{LAYOUT}={SIDE_BY_SIDE}"),
+            title: String::from("Side by side"),
+        },
+    ];
 
     let script = format!("{script}.txt");
-    let script = folder.join(script);
+    let music = format!("{music}.musicxml");
+    let events = format!("/events?path={}&path={}",
+        encode(&folder.join(&script).to_string_lossy()),
+        encode(&folder.join(&music).to_string_lossy()),
+    );
+
+    let script = root.resolve(&folder.join(script))?;
     let script = tokio::fs::read_to_string(&script).await
         .with_context(|| format!("Read from {}", script.display()))?;
 
-    let music = format!("{music}.musicxml");
-    let music = folder.join(music);
+    let music = root.resolve(&folder.join(music))?;
     let music = tokio::fs::read_to_string(&music).await
         .with_context(|| format!("Read from {}", music.display()))?;
 
     let sections = lyric_check::diff::read(&script, &music)?;
-    let page = DiffPage {
+    Ok(DiffPage {
         error: None,
-        folder: String::from(folder_str),
+        folder: title,
+        breadcrumbs: breadcrumbs(folder_str),
         sections,
+        side_by_side,
+        layouts,
+        views,
+        edit: Some(EditScript::new(folder_str, script_name, music_name, &script)),
+        events: Some(events),
+    })
+}
+
+async fn page_script_view(
+    root: &Root,
+    folder: &str,
+    script: &str,
+) -> anyhow::Result<String> {
+    let path = root.resolve(&Path::new(folder).join(format!("{script}.txt")))?;
+    let txt = tokio::fs::read_to_string(&path).await
+        .with_context(|| format!("Read from {}", path.display()))?;
+
+    let words = lyric_check::script::read(&txt)?;
+    let page = ScriptPage {
+        error: None,
+        title: String::from(script),
+        breadcrumbs: breadcrumbs(folder),
+        sections: ScriptSection::new(words),
+    };
+    Ok(page.render().unwrap())
+}
+
+async fn page_music_view(
+    root: &Root,
+    folder: &str,
+    music: &str,
+) -> anyhow::Result<String> {
+    let path = root.resolve(&Path::new(folder).join(format!("{music}.musicxml")))?;
+    let xml = tokio::fs::read_to_string(&path).await
+        .with_context(|| format!("Read from {}", path.display()))?;
+
+    let Some(read) = lyric_check::music::read(&xml)? else {
+        anyhow::bail!("No lyrics in {}", path.display())
+    };
+    let roadmap: Vec<String> = lyric_check::music::roadmap(&xml)?.iter()
+        .map(|segment| segment.to_string())
+        .collect();
+    let page = MusicPage {
+        error: None,
+        title: read.title.unwrap_or_else(|| String::from(music)),
+        breadcrumbs: breadcrumbs(folder),
+        roadmap: roadmap.join(", "),
+        sections: MusicSection::new(read.phrases),
+    };
+    Ok(page.render().unwrap())
+}
+
+fn page_upload(limit: u64, error: Option<String>) -> String {
+    let page = UploadPage {
+        error,
+        breadcrumbs: breadcrumbs(""),
+        limit,
+    };
+    page.render().unwrap()
+}
+
+async fn page_upload_diff(upload: Upload<'_>, side_by_side: bool) -> anyhow::Result<String> {
+    let script = upload_read(&upload.script, "script", &["txt"]).await?;
+    let script = String::from_utf8(script)
+        .map_err(|_| anyhow::anyhow!("Script is not UTF-8 text"))?;
+
+    let music = upload_read(&upload.music, "music", &["musicxml", "mxl", "xml"]).await?;
+    let music = if music.starts_with(b"PK\x03\x04") {
+        lyric_check::mxl::read(&music)?
+    } else {
+        String::from_utf8(music)
+            .map_err(|_| anyhow::anyhow!("Music is neither MUSICXML nor MXL"))?
+    };
+
+    let title = match upload.script.name() {
+        Some(name) => String::from(name),
+        None => String::from("Upload"),
+    };
+    let mut breadcrumbs = breadcrumbs("");
+    breadcrumbs.push(Link {
+        selected: false,
+        href: String::from("/upload"),
+        title: String::from("Upload"),
+    });
+    let page = DiffPage {
+        error: None,
+        folder: title,
+        breadcrumbs,
+        sections: lyric_check::diff::read(&script, &music)?,
+        side_by_side,
+        layouts: Vec::new(),
+        views: Vec::new(),
+        edit: None,
+        events: None,
     };
     Ok(page.render().unwrap())
 }
+
+/// Content of an uploaded file, which must have one of the extensions
+async fn upload_read(
+    file: &TempFile<'_>,
+    what: &str,
+    extensions: &[&str],
+) -> anyhow::Result<Vec<u8>> {
+    let name = file.raw_name()
+        .map(|name| name.dangerous_unsafe_unsanitized_raw().as_str())
+        .unwrap_or("");
+    let extension = Path::new(name).extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+    if file.len() == 0 {
+        anyhow::bail!("No {what} file uploaded");
+    }
+    if !extension.is_some_and(|extension| extensions.contains(&extension.as_str())) {
+        anyhow::bail!("Expecting a {what} file ending .{}, not `{name}`", extensions.join(" or ."));
+    }
+    let mut bytes = Vec::with_capacity(file.len() as usize);
+    file.open().await?.read_to_end(&mut bytes).await
+        .with_context(|| format!("Read from uploaded {what} `{name}`"))?;
+    Ok(bytes)
+}
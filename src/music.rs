@@ -14,20 +14,95 @@ use repeat::{
     Repeats,
     RepeatsBuilder,
 };
+pub use repeat::Segment;
 use syllable::{
     Syllable,
 };
-use verse::Verses;
+pub use verse::Verses;
 
 const CROTCHET: usize = 256;
-const MINIM: usize = CROTCHET + CROTCHET;
+
+/// Rules splitting the words of music into phrases
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct Phrasing {
+    /// Characters that end a phrase at the end of a word
+    pub ends: String,
+    /// Start a phrase at a capital letter after a rest
+    pub capitals: bool,
+    /// Rest, in crotchets, long enough to start a phrase
+    pub rest: Option<usize>,
+    /// Keep the words from `[` to `]` in one phrase
+    pub groups: bool,
+    /// Marks of the score that end a phrase
+    pub marks: Vec<Mark>,
+}
+
+impl Default for Phrasing {
+    fn default() -> Self {
+        Phrasing {
+            ends: String::from(".!"),
+            capitals: true,
+            rest: Some(2),
+            groups: true,
+            marks: Vec::new(),
+        }
+    }
+}
+
+/// Mark of the score at the end of a phrase
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(Eq, PartialEq)]
+pub enum Mark {
+    /// `<breath-mark>` after a note
+    Breath,
+    /// `<fermata>` over a note
+    Fermata,
+    /// Double or final `<bar-style>`
+    Barline,
+    /// `<rehearsal>` mark before a note
+    Rehearsal,
+}
 
 #[derive(Debug)]
-#[derive(PartialEq)]
 pub struct Word {
     pub start: usize,
     pub end: usize,
     pub text: String,
+    /// Number of the bar the word starts in
+    pub bar: usize,
+    /// Pass through a repeat (zero based), if the bar is repeated
+    pub verse: Option<usize>,
+    /// Syllables of the word in each part that sings it
+    pub lyrics: Vec<Vec<Lyric>>,
+}
+
+impl std::cmp::PartialEq for Word {
+    fn eq(&self, other: &Self) -> bool {
+        self.start == other.start
+            && self.end == other.end
+            && self.text == other.text
+            && self.verse == other.verse
+    }
+}
+
+/// Syllable of a word as written in the MUSICXML
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct Lyric {
+    pub source: Source,
+    pub text: String,
+}
+
+/// Byte range of a `<lyric><text>` within the MUSICXML
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(Eq, PartialEq)]
+#[derive(Ord, PartialOrd)]
+pub struct Source {
+    pub from: usize,
+    pub to: usize,
 }
 
 #[derive(Debug)]
@@ -45,24 +120,75 @@ pub struct Part {
 pub struct Music {
     pub title: Option<String>,
     pub phrases: Vec<Phrase>,
+    /// Number of verses of lyrics printed under the staff
+    pub verses: usize,
+    /// Sections of the score, in the order they are written
+    pub headings: Vec<Heading>,
 }
 
+/// Section of the music named by the score, from the bar it starts
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct Heading {
+    /// Rehearsal mark and words naming the section, if any
+    pub name: String,
+    pub bar: usize,
+}
+
+impl std::fmt::Display for Heading {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.name.is_empty() {
+            write!(f, "bar {}", self.bar)
+        } else {
+            write!(f, "{} (bar {})", self.name, self.bar)
+        }
+    }
+}
+
+/// First words of directions that name a section of the music
+const SECTIONS: [&str; 12] = [
+    "bridge",
+    "chorus",
+    "coda",
+    "ending",
+    "interlude",
+    "intro",
+    "introduction",
+    "outro",
+    "pre-chorus",
+    "refrain",
+    "tag",
+    "verse",
+];
+
 pub fn read<'str>(xml: &'str str) -> Result<Option<Music>> {
-    let doc = Document::parse_with_options(xml, roxmltree::ParsingOptions {
-        allow_dtd: true,
-        nodes_limit: u32::MAX,
-    }).with_context(|| format!("Reading MUSICXML {}", &xml[..32]))?;
+    read_lyrics(xml, None, &Phrasing::default())
+}
+
+/// Lyrics of music split into phrases by the rules of `phrasing`
+pub fn read_with(xml: &str, phrasing: &Phrasing) -> Result<Option<Music>> {
+    read_lyrics(xml, None, phrasing)
+}
+
+/// Lyrics of a single verse (zero based) as printed under the staff,
+/// without unrolling repeats, where bars with no lyrics for the verse
+/// sing the first verse
+pub fn read_verse(xml: &str, verse: usize, phrasing: &Phrasing) -> Result<Option<Music>> {
+    read_lyrics(xml, Some(verse), phrasing)
+}
+
+fn read_lyrics(xml: &str, verse: Option<usize>, phrasing: &Phrasing) -> Result<Option<Music>> {
+    let doc = parse(xml)?;
     let root = doc.root_element();
-    if !root.has_tag_name("score-partwise") {
-        bail!("Expecting root <score-partwise> not {}", root.tag_name().name());
-    }
 
     let Some(part) = has_child_element(root, "part") else {
         bail!("No parts found!");
     };
     let repeats = read_bars(part)?;
 
-    let mut builder = Builder::new(repeats);
+    let mut builder = Builder::new(repeats, verse, phrasing.clone());
+    builder.headings(read_headings(part));
 
     if let Some(work) = has_child_element(root, "work") {
         if let Some(title) = has_child_element(work, "work-title") {
@@ -105,6 +231,28 @@ pub fn read<'str>(xml: &'str str) -> Result<Option<Music>> {
     builder.build()
 }
 
+/// Bars of the music in the order they are played, from the repeats
+/// and alternative endings of the first part
+pub fn roadmap(xml: &str) -> Result<Vec<Segment>> {
+    let doc = parse(xml)?;
+    let Some(part) = has_child_element(doc.root_element(), "part") else {
+        bail!("No parts found!");
+    };
+    Ok(read_bars(part)?.roadmap())
+}
+
+fn parse(xml: &str) -> Result<Document<'_>> {
+    let doc = Document::parse_with_options(xml, roxmltree::ParsingOptions {
+        allow_dtd: true,
+        nodes_limit: u32::MAX,
+    }).with_context(|| format!("Reading MUSICXML {}", &xml[..32]))?;
+    let root = doc.root_element();
+    if !root.has_tag_name("score-partwise") {
+        bail!("Expecting root <score-partwise> not {}", root.tag_name().name());
+    }
+    Ok(doc)
+}
+
 fn read_bars(part: Node) -> Result<Repeats> {
     let mut builder: Option<RepeatsBuilder> = None;
 
@@ -133,6 +281,65 @@ fn read_bars(part: Node) -> Result<Repeats> {
     }
 }
 
+/// Sections of the score from rehearsal marks, directions naming a
+/// section like `Chorus`, and double barlines outside repeats
+fn read_headings(part: Node) -> Vec<Heading> {
+    let measures: Vec<Node> = part.children()
+        .filter(|node| node.has_tag_name("measure"))
+        .collect();
+    let mut headings = Vec::new();
+    let mut starts = false;
+    for (index, measure) in measures.iter().enumerate() {
+        let Some(bar) = measure.attribute("number").and_then(|number| usize::from_str(number).ok()) else {
+            continue;
+        };
+        let mut names = Vec::new();
+        let mut ends = false;
+        for node in measure.children() {
+            if node.has_tag_name("barline") {
+                let style = has_child_element(node, "bar-style").and_then(|style| style.text());
+                let double = matches!(style, Some("light-light" | "light-heavy" | "heavy-light" | "heavy-heavy"));
+                if double && has_child_element(node, "repeat").is_none() {
+                    match node.attribute("location") {
+                        Some("left") => starts = true,
+                        _ => ends = true,
+                    }
+                }
+            } else if node.has_tag_name("direction") {
+                for child in node.descendants() {
+                    let Some(text) = child.text().map(str::trim) else {
+                        continue;
+                    };
+                    // like `Chorus:`, to compare with the script's `Chorus`
+                    let name = text.trim_end_matches([':', '.', '-']).trim_end();
+                    if child.has_tag_name("rehearsal") && !name.is_empty()
+                        || child.has_tag_name("words") && is_section(text)
+                    {
+                        names.push(name);
+                    }
+                }
+            }
+        }
+        if starts || !names.is_empty() {
+            headings.push(Heading {
+                name: names.join(" "),
+                bar,
+            });
+        }
+        starts = ends && index + 1 < measures.len();
+    }
+    headings
+}
+
+/// Whether direction text names a section, like `Verse 2` or `Chorus`
+fn is_section(text: &str) -> bool {
+    let Some(word) = text.split_whitespace().next() else {
+        return false;
+    };
+    let word = word.trim_end_matches(|c: char| !c.is_alphanumeric()).to_ascii_lowercase();
+    SECTIONS.contains(&word.as_str())
+}
+
 fn read_bar(builder: &mut RepeatsBuilder, bar: Node) -> Result<()> {
     for node in bar.children() {
         if !node.is_element() {
@@ -145,7 +352,12 @@ fn read_bar(builder: &mut RepeatsBuilder, bar: Node) -> Result<()> {
         } else if node.has_tag_name("barline") {
             read_bar_line(builder, node)?;
         } else if node.has_tag_name("direction") {
-            // Dynamics, tempo, ...
+            // Dynamics, tempo, ... and "Play 3x"
+            for words in node.descendants().filter(|node| node.has_tag_name("words")) {
+                if let Some(times) = words.text().and_then(repeat::times) {
+                    builder.repeat_times(times);
+                }
+            }
         } else if node.has_tag_name("forward") {
             builder.forward(duration_of(node)?);
         } else if node.has_tag_name("harmony") {
@@ -170,16 +382,7 @@ fn read_bar(builder: &mut RepeatsBuilder, bar: Node) -> Result<()> {
 fn read_bar_line(builder: &mut RepeatsBuilder, barline: Node) -> Result<()> {
     match barline.attribute("location") {
         Some("left") => {
-            // repeat before ending
-            if let Some(repeat) = has_child_element(barline, "repeat") {
-                match attribute(repeat, "direction")? {
-                    "forward" =>
-                        (),
-                    direction =>
-                        bail!("<barline location=`left`><repeat direction=`{direction}`>"),
-                }
-                builder.repeat_start()?;
-            }
+            // ending around repeat
             if let Some(ending) = has_child_element(barline, "ending") {
                 match attribute(ending, "type")? {
                     "start" =>
@@ -190,6 +393,15 @@ fn read_bar_line(builder: &mut RepeatsBuilder, barline: Node) -> Result<()> {
                 let verses: Verses = attribute(ending, "number")?.parse()?;
                 builder.ending_start(verses)?;
             }
+            if let Some(repeat) = has_child_element(barline, "repeat") {
+                match attribute(repeat, "direction")? {
+                    "forward" =>
+                        (),
+                    direction =>
+                        bail!("<barline location=`left`><repeat direction=`{direction}`>"),
+                }
+                builder.repeat_start()?;
+            }
         }
         Some("right") => {
             // repeat after ending
@@ -212,6 +424,12 @@ fn read_bar_line(builder: &mut RepeatsBuilder, barline: Node) -> Result<()> {
                     direction =>
                         bail!("<barline location=`right`><repeat direction=`{direction}`>"),
                 }
+                if let Some(times) = repeat.attribute("times") {
+                    let Ok(times) = usize::from_str(times) else {
+                        bail!("<repeat times=`{times}`>")
+                    };
+                    builder.repeat_times(times);
+                }
                 builder.repeat_end()?;
             }
         }
@@ -237,8 +455,15 @@ fn read_part_bar<'xml, 'str: 'xml>(
             builder.backward(duration_of(node)?);
         } else if node.has_tag_name("barline") {
             // repeats already read
+            let style = has_child_element(node, "bar-style").and_then(|style| style.text());
+            if let Some("light-light" | "light-heavy" | "heavy-light" | "heavy-heavy") = style {
+                builder.mark(Mark::Barline, 0);
+            }
         } else if node.has_tag_name("direction") {
             // Dynamics, tempo, ...
+            if node.descendants().any(|node| node.has_tag_name("rehearsal")) {
+                builder.mark(Mark::Rehearsal, 0);
+            }
         } else if node.has_tag_name("forward") {
             builder.forward(duration_of(node)?);
         } else if node.has_tag_name("harmony") {
@@ -296,10 +521,18 @@ fn read_part_note<'xml, 'str: 'xml>(
         } else {
             bail!("<lyric number=`{verse}`>");
         };
-        let text = child_element_text(lyric, "text")?;
+        let (text, source) = child_element_source(lyric, "text")?;
         let kind = child_element_text(lyric, "syllabic")?;
         let kind = syllable::Kind::from_str(kind)?;
-        builder.lyric(voice, verse, kind, text, duration);
+        builder.lyric(voice, verse, kind, text, source, duration);
+    }
+    if let Some(notations) = has_child_element(note, "notations") {
+        if notations.descendants().any(|node| node.has_tag_name("fermata")) {
+            builder.mark(Mark::Fermata, duration);
+        }
+        if notations.descendants().any(|node| node.has_tag_name("breath-mark")) {
+            builder.mark(Mark::Breath, duration);
+        }
     }
     builder.forward(duration);
     Ok(())
@@ -336,6 +569,31 @@ fn child_element_text<'xml, 'str: 'xml>(
     bail!("Expecting child <{name}> in <{tag}>")
 }
 
+fn child_element_source<'xml, 'str: 'xml>(
+    node: Node<'xml, 'str>,
+    name: &str,
+) -> Result<(&'xml str, Source)> {
+    for child in node.children() {
+        if child.has_tag_name(name) {
+            let range = match child.first_child() {
+                Some(text) if text.is_text() =>
+                    text.range(),
+
+                _ =>
+                    child.range().end..child.range().end,
+            };
+            let source = Source {
+                from: range.start,
+                to: range.end,
+            };
+            return Ok((child.text().unwrap_or(""), source));
+        }
+    }
+
+    let tag = node.tag_name().name();
+    bail!("Expecting child <{name}> in <{tag}>")
+}
+
 fn attribute<'xml, 'str: 'xml>(
     node: Node<'xml, 'str>,
     name: &str,
@@ -360,6 +618,11 @@ fn duration_of(node: Node) -> Result<usize> {
 struct Builder<'xml> {
     title: Option<String>,
     repeats: Repeats,
+    /// Single verse to read, instead of unrolling the repeats
+    verse: Option<usize>,
+    verses: usize,
+    phrasing: Phrasing,
+    headings: Vec<Heading>,
     next_number: usize,
     bar_tick: usize,
     syllables: syllable::Builder<'xml>,
@@ -367,12 +630,16 @@ struct Builder<'xml> {
 }
 
 impl<'dom> Builder<'dom> {
-    fn new(repeats: Repeats) -> Self {
+    fn new(repeats: Repeats, verse: Option<usize>, phrasing: Phrasing) -> Self {
         let next_number = repeats.first_bar_number();
         let bar_count = repeats.bar_count();
         Builder {
             title: None,
             repeats,
+            verse,
+            verses: 0,
+            phrasing,
+            headings: Vec::new(),
             next_number,
             bar_tick: 0,
             syllables: syllable::Builder::new(bar_count),
@@ -384,10 +651,14 @@ impl<'dom> Builder<'dom> {
         self.title = Some(String::from(title));
     }
 
+    fn headings(&mut self, headings: Vec<Heading>) {
+        self.headings = headings;
+    }
+
     fn part_end(&mut self) {
         self.next_number = self.repeats.first_bar_number();
         self.bar_tick = 0;
-        if let Some(part) = self.syllables.part_end(&self.repeats) {
+        if let Some(part) = self.syllables.part_end(&self.repeats, self.verse, &self.phrasing) {
             self.parts.push(part);
         }
     }
@@ -410,20 +681,28 @@ impl<'dom> Builder<'dom> {
         self.bar_tick += duration;
     }
 
+    /// Mark of the score `offset` ticks into the bar from here
+    fn mark(&mut self, mark: Mark, offset: usize) {
+        self.syllables.mark(mark, self.bar_tick + offset);
+    }
+
     fn lyric(
         &mut self,
         voice: usize,
         verse: usize,
         kind: syllable::Kind,
         text: &'dom str,
+        source: Source,
         duration: usize,
     ) {
+        self.verses = self.verses.max(verse + 1);
         self.syllables.lyric(voice, verse, syllable::Syllable {
             start: self.bar_tick,
             voice,
             end: self.bar_tick + duration,
             kind,
             text,
+            source,
         });
     }
 
@@ -439,6 +718,8 @@ impl<'dom> Builder<'dom> {
             Ok(Some(Music {
                 title: self.title,
                 phrases: part.phrases,
+                verses: self.verses,
+                headings: self.headings,
             }))
         } else {
             Ok(None)
@@ -448,11 +729,14 @@ impl<'dom> Builder<'dom> {
 
 impl Part {
     fn merge(&mut self, other: Phrase, from: usize) -> usize {
-        for (index, phrase) in self.phrases.iter().enumerate() {
+        for (index, phrase) in self.phrases.iter_mut().enumerate() {
             if index < from {
                 continue;
             }
             if phrase == &other {
+                for (word, other) in phrase.words.iter_mut().zip(other.words) {
+                    word.lyrics.extend(other.lyrics);
+                }
                 return index;
             }
             if other.start < phrase.start && other.end < phrase.end {
@@ -475,3 +759,94 @@ impl Part {
 //     }
 //     println!("]");
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(text: &str, notations: &str) -> String {
+        format!(concat!(
+            "<note><duration>256</duration><voice>1</voice>{}",
+            "<lyric number=\"verse1\"><syllabic>single</syllabic><text>{}</text></lyric>",
+            "</note>",
+        ), notations, text)
+    }
+
+    fn phrases(xml: &str, phrasing: &Phrasing) -> Result<Vec<String>> {
+        let Some(music) = read_with(xml, phrasing)? else {
+            bail!("No lyrics");
+        };
+        Ok(music.phrases.iter().map(|phrase| {
+            let words: Vec<&str> = phrase.words.iter()
+                .map(|word| word.text.as_str())
+                .collect();
+            words.join(" ")
+        }).collect())
+    }
+
+    fn direction(kind: &str, text: &str) -> String {
+        format!("<direction><direction-type><{kind}>{text}</{kind}></direction-type></direction>")
+    }
+
+    #[test]
+    fn test_headings() -> Result<()> {
+        let xml = [
+            "<score-partwise><part id=\"P1\"><measure number=\"1\">",
+            &direction("rehearsal", "A"),
+            &direction("words", "Verse 1"),
+            &note("Sing", ""),
+            "</measure><measure number=\"2\">",
+            &note("high", ""),
+            "<barline location=\"right\"><bar-style>light-light</bar-style></barline>",
+            "</measure><measure number=\"3\">",
+            &direction("words", "rit."),
+            &note("sing", ""),
+            "</measure><measure number=\"4\">",
+            &direction("words", "Chorus:"),
+            &note("low", ""),
+            "<barline location=\"right\"><bar-style>light-heavy</bar-style></barline>",
+            "</measure></part></score-partwise>",
+        ].concat();
+        let Some(music) = read(&xml)? else {
+            bail!("No lyrics");
+        };
+        let headings: Vec<String> = music.headings.iter()
+            .map(|heading| heading.to_string())
+            .collect();
+        assert_eq!(headings, ["A Verse 1 (bar 1)", "bar 3", "Chorus (bar 4)"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_phrasing() -> Result<()> {
+        let xml = [
+            "<score-partwise><part id=\"P1\"><measure number=\"1\">",
+            &note("sing", ""),
+            &note("high;", "<notations><articulations><breath-mark/></articulations></notations>"),
+            &note("sing", ""),
+            &note("low", ""),
+            "<barline location=\"right\"><bar-style>light-light</bar-style></barline>",
+            "</measure><measure number=\"2\">",
+            &note("sing", ""),
+            "<direction><direction-type><rehearsal>A</rehearsal></direction-type></direction>",
+            &note("on", "<notations><fermata/></notations>"),
+            &note("Amen", ""),
+            "</measure></part></score-partwise>",
+        ].concat();
+
+        assert_eq!(phrases(&xml, &Phrasing::default())?, ["sing high; sing low sing on Amen"]);
+
+        let phrasing = Phrasing {
+            ends: String::from(".!;"),
+            ..Phrasing::default()
+        };
+        assert_eq!(phrases(&xml, &phrasing)?, ["sing high;", "sing low sing on Amen"]);
+
+        let phrasing = Phrasing {
+            marks: vec![Mark::Breath, Mark::Barline, Mark::Rehearsal, Mark::Fermata],
+            ..Phrasing::default()
+        };
+        assert_eq!(phrases(&xml, &phrasing)?, ["sing high;", "sing low", "sing", "on", "Amen"]);
+        Ok(())
+    }
+}
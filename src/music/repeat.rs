@@ -24,6 +24,46 @@ pub struct Repeats {
     first_bar_number: usize,
     durations: Vec<usize>,
     repeats: Vec<Repeat>,
+    /// Repeat written outside any other that each of `repeats` is played
+    /// within, numbered from zero
+    outer: Vec<Option<usize>>,
+    /// Bars at the start of an alternative ending
+    endings: BTreeMap<(usize, usize), Verses>,
+}
+
+/// Bars played in a row, numbered as in the score
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct Segment {
+    pub first: usize,
+    pub last: usize,
+    /// Pass through a repeat (zero based), if the bars are repeated
+    pub verse: Option<usize>,
+    /// Passes through a repeat that play the bars as their ending
+    pub ending: Option<Verses>,
+    /// Repeat written outside any other (zero based) the bars are played
+    /// within, so the segments of one repeat can be told from the next
+    pub repeat: Option<usize>,
+}
+
+impl std::fmt::Display for Segment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.first == self.last {
+            write!(f, "{}", self.first)?;
+        } else {
+            write!(f, "{}-{}", self.first, self.last)?;
+        }
+        match (&self.ending, self.verse) {
+            (Some(ending), _) =>
+                write!(f, " ({ending})"),
+
+            (None, Some(verse)) =>
+                write!(f, " (v{})", verse + 1),
+
+            (None, None) =>
+                Ok(()),
+        }
+    }
 }
 
 impl Repeats {
@@ -35,6 +75,17 @@ impl Repeats {
         self.durations.len()
     }
 
+    /// Bars in the order they are played
+    pub fn roadmap(&self) -> Vec<Segment> {
+        self.repeats.iter().zip(&self.outer).map(|(repeat, outer)| Segment {
+            first: repeat.bars.start + self.first_bar_number,
+            last: repeat.bars.end - 1 + self.first_bar_number,
+            verse: repeat.verse,
+            ending: self.endings.get(&(repeat.bars.start, repeat.bars.end)).cloned(),
+            repeat: *outer,
+        }).collect()
+    }
+
     pub fn bars(&self) -> Bars<'_> {
         Bars {
             durations: &self.durations,
@@ -44,6 +95,17 @@ impl Repeats {
             tick: 0,
         }
     }
+
+    /// Bars in the order they are written, each once
+    pub fn written(&self) -> Bars<'_> {
+        Bars {
+            durations: &self.durations,
+            repeats: [].iter(),
+            verse: None,
+            indexes: Range { start: 0, end: self.durations.len() },
+            tick: 0,
+        }
+    }
 }
 
 pub struct Bars<'a> {
@@ -85,31 +147,59 @@ pub struct RepeatsBuilder {
     durations: Vec<usize>,
     duration: usize,
     max_duration: usize,
+    /// First bar not yet added to the music
+    from: usize,
+    /// Music outside any repeat
+    top: Vec<Node>,
+    /// Repeats started and not yet finished, innermost last
+    stack: Vec<Open>,
+    /// Times the repeat being read is played, if marked; outside the body
+    /// of a repeat, only kept to the end of the bar
+    times: Option<usize>,
+}
+
+/// Music as written, before repeats are unrolled
+#[derive(Debug)]
+enum Node {
+    Bars(Range<usize>),
+    Repeat {
+        /// Last bar, for messages
+        end: usize,
+        body: Vec<Node>,
+        endings: Vec<Ending>,
+        times: usize,
+    },
+}
+
+/// Alternative ending (volta) of a repeat
+#[derive(Debug)]
+struct Ending {
+    verses: Verses,
+    start: usize,
+    nodes: Vec<Node>,
+}
+
+/// Repeat started and not yet finished
+#[derive(Debug)]
+struct Open {
+    start: usize,
+    body: Vec<Node>,
+    endings: Vec<Ending>,
+    times: usize,
     state: RepeatBuilder,
-    common: Range<usize>,
-    endings: BTreeMap<usize, Repeat>,
-    repeats: Vec<Repeat>,
 }
 
 #[derive(Debug)]
 enum RepeatBuilder {
-    Normal {
-        bar: usize,
-    },
-    RepeatStart {
-        bar: usize,
-    },
-    EndingStart {
-        bar: usize,
-        verses: Verses,
-    },
-    EndingStop {
-        bar: usize,
-        verses: Verses,
-    },
-    RepeatStop {
-        bar: usize,
-    },
+    /// Reading the bars played every time
+    Body,
+    /// Reading an alternative ending
+    EndingStart(Ending),
+    /// Read the end of an alternative ending, which an end of repeat
+    /// may follow
+    EndingStop,
+    /// Read the end of repeat, which alternative endings may follow
+    RepeatStop,
 }
 
 impl RepeatsBuilder {
@@ -120,15 +210,10 @@ impl RepeatsBuilder {
             durations: Vec::new(),
             duration: 0,
             max_duration: 0,
-            state: RepeatBuilder::Normal {
-                bar: 0,
-            },
-            common: Range {
-                start: 0,
-                end: 0,
-            },
-            endings: BTreeMap::new(),
-            repeats: Vec::new(),
+            from: 0,
+            top: Vec::new(),
+            stack: Vec::new(),
+            times: None,
         }
     }
 
@@ -141,6 +226,9 @@ impl RepeatsBuilder {
         self.bar += 1;
         self.duration = 0;
         self.max_duration = 0;
+        if !matches!(self.stack.last(), Some(Open { state: RepeatBuilder::Body, .. })) {
+            self.times = None;
+        }
         Ok(())
     }
 
@@ -158,313 +246,339 @@ impl RepeatsBuilder {
         }
     }
 
-    pub fn repeat_start(&mut self) -> Result<()> {
-        match &self.state {
-            RepeatBuilder::Normal { bar } => {
-                if self.bar > *bar {
-                    self.repeats.push(Repeat {
-                        verse: None,
-                        bars: Range {
-                            start: *bar,
-                            end: self.bar,
-                        },
-                    });
-                }
-                self.state = RepeatBuilder::RepeatStart {
-                    bar: self.bar,
-                };
-            },
+    fn number(&self, bar: usize) -> usize {
+        bar + self.bar_to_number
+    }
 
-            RepeatBuilder::RepeatStop { bar } => {
-                let after = Range {
-                    start: *bar,
-                    end: self.bar,
-                };
-                self.repeat_open(after)?;
-                self.state = RepeatBuilder::RepeatStart {
-                    bar: self.bar,
-                };
-            }
+    /// Music being read by the innermost repeat, or outside any repeat
+    fn nodes(&mut self) -> &mut Vec<Node> {
+        match self.stack.last_mut() {
+            Some(Open { state: RepeatBuilder::EndingStart(ending), .. }) =>
+                &mut ending.nodes,
+
+            Some(open) =>
+                &mut open.body,
 
-            _ =>
-                bail!("BAR {} REPEAT START {:?}", self.bar, self.state),
+            None =>
+                &mut self.top,
         }
-        Ok(())
     }
 
-    pub fn ending_start(&mut self, verses: Verses) -> Result<()> {
-        match &self.state {
-            RepeatBuilder::Normal { bar } if *bar > 0 =>
-                bail!("Ending at bar {} with no start of repeat",
-                    self.bar + 1 + self.bar_to_number,
-                ),
-
-            RepeatBuilder::Normal { bar } |
-            RepeatBuilder::RepeatStart { bar } => {
-                self.common = Range {
-                    start: *bar,
-                    end: self.bar,
-                };
-                self.endings.clear();
-                self.state = RepeatBuilder::EndingStart {
-                    bar: self.bar,
-                    verses,
-                };
-            }
+    /// Add the bars read since the last start or end of repeat or ending
+    fn flush(&mut self, to: usize) {
+        if self.from < to {
+            let bars = Range {
+                start: self.from,
+                end: to,
+            };
+            self.nodes().push(Node::Bars(bars));
+        }
+        self.from = to;
+    }
 
-            RepeatBuilder::RepeatStop { bar } => {
-                if self.bar != *bar {
-                    bail!("Alternative ending must start straight after end of repeat at bar {}",
-                        self.bar + self.bar_to_number,
-                    )
-                }
-                if self.endings.is_empty() && verses == Verses::TWO {
-                    self.endings.insert(0, Repeat {
-                        verse: None,
-                        bars: Range {
-                            start: *bar,
-                            end: *bar,
-                        },
-                    });
-                }
-                self.state = RepeatBuilder::EndingStart {
-                    bar: *bar,
-                    verses,
-                };
-            }
+    /// Whether the innermost repeat has ended, leaving only alternative
+    /// endings to follow it
+    fn is_stopped(&self) -> bool {
+        matches!(
+            self.stack.last(),
+            Some(Open { state: RepeatBuilder::RepeatStop | RepeatBuilder::EndingStop, .. }),
+        )
+    }
 
-            _ =>
-                bail!("BAR {} ENDING START {:?} VERSE {verses:?}", self.bar, self.state),
+    /// Add the innermost repeat to the music around it
+    fn finish(&mut self) {
+        if let Some(open) = self.stack.pop() {
+            let end = self.from.saturating_sub(1);
+            self.nodes().push(Node::Repeat {
+                end,
+                body: open.body,
+                endings: open.endings,
+                times: open.times,
+            });
         }
-        Ok(())
     }
 
-    pub fn ending_end(&mut self, verses: Verses, last: bool) -> Result<()> {
-        match &self.state {
-            RepeatBuilder::EndingStart { bar, verses: expect } => {
-                assert_eq!(verses, *expect);
-                if let Some(verse) = verses.to_single() {
-                    if let Some(dup) = self.endings.get(&verse) {
-                        bail!("Ending {} at bar {} is duplicated as bar {}",
-                            verse + 1,
-                            dup.bars.start + self.bar_to_number,
-                            bar + self.bar_to_number,
-                        )
-                    }
-                    self.endings.insert(verse, Repeat {
-                        verse: None,
-                        bars: Range {
-                            start: *bar,
-                            end: self.bar + 1,
-                        },
-                    });
-                } else {
-                    for (ending, verse) in verses.clone().enumerate() {
-                        if let Some(dup) = self.endings.get(&verse) {
-                            bail!("Ending {} at bar {} is duplicated as bar {}",
-                                verse + 1,
-                                dup.bars.start + self.bar_to_number,
-                                bar + self.bar_to_number,
-                            )
-                        }
-                        self.endings.insert(verse, Repeat {
-                            verse: Some(ending),
-                            bars: Range {
-                                start: *bar,
-                                end: self.bar + 1,
-                            },
-                        });
-                    }
-                }
-                if last {
-                    if let Some(last) = verses.to_single() {
-                        self.repeat_closed(0..last + 1)?;
-                        self.state = RepeatBuilder::Normal {
-                            bar: self.bar + 1,
-                        };
-                    } else {
-                        bail!("Final ending at bar {} must be singular",
-                            bar + self.bar_to_number,
-                        );
-                    }
-                } else {
-                    self.state = RepeatBuilder::EndingStop {
-                        bar: self.bar + 1,
-                        verses,
-                    };
-                }
-            }
-
-            _ =>
-                bail!("BAR {} ENDING STOP {:?} VERSE {verses:?} LAST {last}", self.bar, self.state),
+    pub fn repeat_start(&mut self) -> Result<()> {
+        let at = self.bar;
+        while self.is_stopped() {
+            self.flush_stopped(at);
         }
+        self.flush(at);
+        self.stack.push(Open {
+            start: at,
+            body: Vec::new(),
+            endings: Vec::new(),
+            times: 2,
+            state: RepeatBuilder::Body,
+        });
         Ok(())
     }
 
-    pub fn repeat_end(&mut self) -> Result<()> {
-        match self.state {
-            RepeatBuilder::Normal { bar } if bar > 0 =>
-                bail!("End of repeat at bar {} with no start of repeat",
-                    self.bar + self.bar_to_number,
-                ),
-
-            RepeatBuilder::Normal { bar } |
-            RepeatBuilder::RepeatStart { bar } => {
-                self.common = Range {
-                    start: bar,
-                    end: self.bar + 1,
-                };
-                self.endings.clear();
-                self.state = RepeatBuilder::RepeatStop {
-                    bar: self.bar + 1,
-                };
-            }
-
-            RepeatBuilder::EndingStop { bar, .. } => {
-                if bar != self.bar + 1 {
-                    bail!("Gap between ending at bar {} and repeat at bar {}",
-                        bar + self.bar_to_number,
-                        self.bar + self.bar_to_number,
-                    )
-                }
-                self.state = RepeatBuilder::RepeatStop {
-                    bar,
-                };
-            }
+    /// Finish the innermost stopped repeat, adding any bars read after it
+    /// to the music around it
+    fn flush_stopped(&mut self, to: usize) {
+        self.finish();
+        self.flush(to);
+    }
 
-            _ =>
-                bail!("BAR {} REPEAT END {:?}", self.bar, self.state),
+    pub fn ending_start(&mut self, verses: Verses) -> Result<()> {
+        let at = self.bar;
+        // bars after a stopped repeat, or a first ending straight after
+        // it, cannot be one of its endings
+        let after = self.from < at || matches!(
+            self.stack.last(),
+            Some(Open { state: RepeatBuilder::RepeatStop, endings, .. })
+            if endings.is_empty() && verses.contains(0),
+        );
+        if self.is_stopped() && after {
+            self.flush_stopped(at);
         }
-        Ok(())
-    }
+        match self.stack.last().map(|open| &open.state) {
+            None =>
+                bail!("Ending at bar {} with no start of repeat", self.number(at)),
 
-    fn repeat_open(&mut self, after: Range<usize>) -> Result<()> {
-        if let Some((last, _)) = self.endings.last_key_value() {
-            self.repeat_closed(0..last + 1)?;
-        } else {
-            // assume repeat twice
-            assert!(!self.common.is_empty());
-            self.repeats.push(Repeat {
-                verse: Some(0),
-                bars: self.common.clone(),
-            });
-            self.repeats.push(Repeat {
-                verse: Some(1),
-                bars: self.common.clone(),
-            });
+            Some(RepeatBuilder::EndingStart(ending)) =>
+                bail!("Ending at bar {} starts within ending at bar {}",
+                    self.number(at),
+                    self.number(ending.start),
+                ),
+
+            Some(_) =>
+                (),
         }
-        if !after.is_empty() {
-            self.repeats.push(Repeat {
-                verse: None,
-                bars: after,
+        self.flush(at);
+        let Some(open) = self.stack.last_mut() else {
+            unreachable!("repeat started");
+        };
+        if matches!(open.state, RepeatBuilder::RepeatStop)
+            && open.endings.is_empty()
+            && !verses.contains(0)
+        {
+            // played straight through the first time
+            open.endings.push(Ending {
+                verses: Verses::ONE,
+                start: at,
+                nodes: Vec::new(),
             });
         }
+        open.state = RepeatBuilder::EndingStart(Ending {
+            verses,
+            start: at,
+            nodes: Vec::new(),
+        });
         Ok(())
     }
 
-    fn repeat_closed(&mut self, verses: Range<usize>) -> Result<()> {
+    pub fn ending_end(&mut self, verses: Verses, last: bool) -> Result<()> {
+        let to = self.bar + 1;
+        while self.is_stopped() {
+            self.flush_stopped(to);
+        }
+        self.flush(to);
+        let number = self.number(self.bar);
+        let Some(open) = self.stack.last_mut() else {
+            bail!("End of ending at bar {number} with no start of ending");
+        };
+        let state = std::mem::replace(&mut open.state, RepeatBuilder::EndingStop);
+        let RepeatBuilder::EndingStart(ending) = state else {
+            bail!("End of ending at bar {number} with no start of ending");
+        };
+        if ending.verses != verses {
+            bail!("End of ending {verses:?} at bar {number} does not match start {:?}",
+                ending.verses,
+            );
+        }
         for verse in verses {
-            if !self.common.is_empty() {
-                self.repeats.push(Repeat {
-                    verse: Some(verse),
-                    bars: self.common.clone(),
-                });
-            }
-            if let Some(ending) = self.endings.remove(&verse) {
-                self.repeats.push(ending);
-            } else {
-                bail!("Repeat at bar {} missing ending {}",
-                    self.bar + self.bar_to_number,
+            if let Some(dup) = open.endings.iter().find(|dup| dup.verses.contains(verse)) {
+                bail!("Ending {} at bar {} is duplicated as bar {}",
                     verse + 1,
+                    dup.start + self.bar_to_number,
+                    ending.start + self.bar_to_number,
                 )
             }
         }
+        open.endings.push(ending);
+        if last {
+            self.finish();
+        }
         Ok(())
     }
 
-    pub fn build(mut self) -> Result<Repeats> {
-        self.durations.push(self.max_duration);
-        match &self.state {
-            RepeatBuilder::Normal { bar } => {
-                if self.bar >= *bar {
-                    self.repeats.push(Repeat {
-                        verse: None,
-                        bars: Range {
-                            start: *bar,
-                            end: self.bar + 1,
-                        },
-                    });
-                }
-            }
+    /// Mark how many times the repeat being read is played
+    pub fn repeat_times(&mut self, times: usize) {
+        self.times = Some(times);
+    }
 
-            RepeatBuilder::RepeatStart { bar } if *bar > 0 =>
-                bail!("Start of repeat at bar {} with no end of repeat",
-                    *bar + self.bar_to_number,
+    pub fn repeat_end(&mut self) -> Result<()> {
+        let times = self.times.take().unwrap_or(2);
+        let to = self.bar + 1;
+        // an end of repeat straight after another belongs to an outer one
+        while matches!(self.stack.last(), Some(Open { state: RepeatBuilder::RepeatStop, .. })) {
+            self.flush_stopped(to);
+        }
+        if self.is_stopped() && self.from < to {
+            self.flush_stopped(to);
+        }
+        if self.stack.is_empty() {
+            bail!("End of repeat at bar {} with no start of repeat", self.number(self.bar));
+        }
+        self.flush(to);
+        let number = self.number(self.bar);
+        let Some(open) = self.stack.last_mut() else {
+            unreachable!("repeat started");
+        };
+        match &open.state {
+            RepeatBuilder::Body =>
+                open.times = times,
+
+            RepeatBuilder::EndingStop =>
+                (),
+
+            RepeatBuilder::EndingStart(ending) =>
+                bail!("End of repeat at bar {number} within ending at bar {}",
+                    ending.start + self.bar_to_number,
                 ),
 
-            RepeatBuilder::EndingStart { bar, verses } => {
-                if let Some(verse) = verses.to_single() {
-                    if let Some(dup) = self.endings.get(&verse) {
-                        bail!("Ending {} at bar {} is duplicated as bar {}",
-                            verse + 1,
-                            dup.bars.start + self.bar_to_number,
-                            bar + self.bar_to_number,
-                        )
-                    }
-                    self.endings.insert(verse, Repeat {
-                        verse: None,
-                        bars: Range {
-                            start: *bar,
-                            end: self.bar + 1,
-                        },
-                    });
-                    self.repeat_closed(0..verse + 1)?;
-                } else {
-                    bail!("Final ending at bar {} must be singular",
-                        bar + self.bar_to_number,
-                    );
-                }
-            }
+            RepeatBuilder::RepeatStop =>
+                unreachable!("finished"),
+        }
+        open.state = RepeatBuilder::RepeatStop;
+        Ok(())
+    }
+
+    pub fn build(mut self) -> Result<Repeats> {
+        self.durations.push(self.max_duration);
+        let to = self.bar + 1;
+        while let Some(open) = self.stack.last_mut() {
+            match &mut open.state {
+                RepeatBuilder::Body =>
+                    bail!("Start of repeat at bar {} with no end of repeat",
+                        open.start + self.bar_to_number,
+                    ),
 
-            RepeatBuilder::EndingStop { bar, verses } => {
-                let bar = *bar;
-                if let Some(last) = verses.to_single() {
-                    self.repeat_closed(0..last + 1)?;
-                    if self.bar >= bar {
-                        self.repeats.push(Repeat {
-                            verse: None,
-                            bars: Range {
-                                start: bar,
-                                end: self.bar + 1,
-                            },
-                        });
+                RepeatBuilder::EndingStart(_) => {
+                    // ending to the last bar
+                    self.flush(to);
+                    let Some(open) = self.stack.last_mut() else {
+                        unreachable!("repeat started");
+                    };
+                    let state = std::mem::replace(&mut open.state, RepeatBuilder::EndingStop);
+                    if let RepeatBuilder::EndingStart(ending) = state {
+                        open.endings.push(ending);
                     }
-                } else {
-                    bail!("Final ending at bar {} must be singular",
-                        bar + self.bar_to_number,
-                    );
+                    self.finish();
                 }
-            }
 
-            RepeatBuilder::RepeatStop { bar } => {
-                let after = Range {
-                    start: *bar,
-                    end: self.bar + 1,
-                };
-                self.repeat_open(after)?;
+                RepeatBuilder::EndingStop | RepeatBuilder::RepeatStop =>
+                    self.flush_stopped(to),
             }
+        }
+        self.flush(to);
 
-            _ =>
-                bail!("BARS END {:?}", self.state),
+        let mut segments = Vec::new();
+        let mut outer = Vec::new();
+        let mut count = 0;
+        for node in &self.top {
+            unroll(std::slice::from_ref(node), &mut segments, self.bar_to_number)?;
+            let repeat = match node {
+                Node::Repeat { .. } => {
+                    count += 1;
+                    Some(count - 1)
+                }
+                Node::Bars(_) =>
+                    None,
+            };
+            outer.resize(segments.len(), repeat);
+        }
+        let mut counts: BTreeMap<(usize, usize), (usize, usize)> = BTreeMap::new();
+        for bars in &segments {
+            counts.entry((bars.start, bars.end)).or_default().1 += 1;
         }
+        let mut repeats = Vec::with_capacity(segments.len());
+        for bars in segments {
+            let (played, total) = counts.get_mut(&(bars.start, bars.end)).unwrap();
+            let verse = if *total > 1 {
+                Some(*played)
+            } else {
+                None
+            };
+            *played += 1;
+            repeats.push(Repeat {
+                verse,
+                bars,
+            });
+        }
+
+        let mut endings = BTreeMap::new();
+        ending_bars(&self.top, &mut endings);
+
         Ok(Repeats {
             first_bar_number: self.bar_to_number,
             durations: self.durations,
-            repeats: self.repeats,
+            repeats,
+            outer,
+            endings,
         })
     }
 }
 
+/// Bars at the start of each alternative ending
+fn ending_bars(nodes: &[Node], endings: &mut BTreeMap<(usize, usize), Verses>) {
+    for node in nodes {
+        if let Node::Repeat { body, endings: repeat_endings, .. } = node {
+            ending_bars(body, endings);
+            for ending in repeat_endings {
+                if let Some(Node::Bars(bars)) = ending.nodes.first() {
+                    endings.insert((bars.start, bars.end), ending.verses.clone());
+                }
+                ending_bars(&ending.nodes, endings);
+            }
+        }
+    }
+}
+
+/// Bars in the order they are played
+fn unroll(nodes: &[Node], segments: &mut Vec<Range<usize>>, bar_to_number: usize) -> Result<()> {
+    for node in nodes {
+        match node {
+            Node::Bars(bars) =>
+                segments.push(bars.clone()),
+
+            Node::Repeat { end, body, endings, times } => {
+                let passes = endings.iter()
+                    .filter_map(|ending| ending.verses.clone().max())
+                    .max()
+                    .map_or(*times, |last| last + 1);
+                for pass in 0..passes {
+                    unroll(body, segments, bar_to_number)?;
+                    if endings.is_empty() {
+                        continue;
+                    }
+                    let Some(ending) = endings.iter().find(|ending| ending.verses.contains(pass)) else {
+                        bail!("Repeat at bar {} missing ending {}",
+                            end + bar_to_number,
+                            pass + 1,
+                        )
+                    };
+                    unroll(&ending.nodes, segments, bar_to_number)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Times a repeat is played from direction text like `Play 3x` or `x4`
+pub fn times(text: &str) -> Option<usize> {
+    text.split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\u{d7}'))
+        .find_map(|word| {
+            let number = word.strip_suffix(['x', 'X', '\u{d7}'])
+                .or_else(|| word.strip_prefix(['x', 'X', '\u{d7}']))?;
+            number.parse().ok().filter(|times| *times > 1)
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
@@ -612,6 +726,212 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_repeat_times() -> Result<()> {
+        let mut repeats = new_repeat_start(2)?;
+        repeats.repeat_times(3);
+        repeats.repeat_end()?;
+        normal(&mut repeats, 2..4)?;
+        repeat(&mut repeats, 4..6)?;
+        // build
+        let repeats = repeats.build()?;
+        assert_eq!(repeats.repeats, vec![
+            Repeat { verse: Some(0), bars: 0..2 },
+            Repeat { verse: Some(1), bars: 0..2 },
+            Repeat { verse: Some(2), bars: 0..2 },
+            Repeat { verse: None, bars: 2..4 },
+            Repeat { verse: Some(0), bars: 4..6 },
+            Repeat { verse: Some(1), bars: 4..6 },
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_repeat_times_outside_repeat() -> Result<()> {
+        let mut repeats = RepeatsBuilder::new(1);
+        repeats.forward(1024);
+        // like "Clap 2x" before the repeat
+        repeats.repeat_times(3);
+        repeat(&mut repeats, 1..3)?;
+        let repeats = repeats.build()?;
+        assert_eq!(repeats.repeats, vec![
+            Repeat { verse: None, bars: 0..1 },
+            Repeat { verse: Some(0), bars: 1..3 },
+            Repeat { verse: Some(1), bars: 1..3 },
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_times() {
+        assert_eq!(times("Play 3x"), Some(3));
+        assert_eq!(times("(x4)"), Some(4));
+        assert_eq!(times("Vamp 6X till ready"), Some(6));
+        assert_eq!(times("3\u{d7}"), Some(3));
+        assert_eq!(times("Last x"), None);
+        assert_eq!(times("1x only"), None);
+        assert_eq!(times("Freely"), None);
+    }
+
+    #[test]
+    fn test_repeat_end_after_repeat() -> Result<()> {
+        let mut repeats = new_repeat(2)?;
+        normal(&mut repeats, 2..4)?;
+        assert!(repeats.repeat_end().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_repeat() -> Result<()> {
+        let mut repeats = new_repeat_start(2)?;
+        repeat(&mut repeats, 2..4)?;
+        normal(&mut repeats, 4..6)?;
+        repeats.repeat_end()?;
+        // build
+        let repeats = repeats.build()?;
+        assert_eq!(repeats.repeats, vec![
+            Repeat { verse: Some(0), bars: 0..2 },
+            Repeat { verse: Some(0), bars: 2..4 },
+            Repeat { verse: Some(1), bars: 2..4 },
+            Repeat { verse: Some(0), bars: 4..6 },
+            Repeat { verse: Some(1), bars: 0..2 },
+            Repeat { verse: Some(2), bars: 2..4 },
+            Repeat { verse: Some(3), bars: 2..4 },
+            Repeat { verse: Some(1), bars: 4..6 },
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_repeat_same_end() -> Result<()> {
+        let mut repeats = new_repeat_start(2)?;
+        repeat(&mut repeats, 2..4)?;
+        repeats.repeat_end()?;
+        normal(&mut repeats, 4..6)?;
+        // build
+        let repeats = repeats.build()?;
+        assert_eq!(repeats.repeats, vec![
+            Repeat { verse: Some(0), bars: 0..2 },
+            Repeat { verse: Some(0), bars: 2..4 },
+            Repeat { verse: Some(1), bars: 2..4 },
+            Repeat { verse: Some(1), bars: 0..2 },
+            Repeat { verse: Some(2), bars: 2..4 },
+            Repeat { verse: Some(3), bars: 2..4 },
+            Repeat { verse: None, bars: 4..6 },
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_repeat_first_second() -> Result<()> {
+        let mut repeats = new_repeat_start(2)?;
+        repeat(&mut repeats, 2..4)?;
+        ending(&mut repeats, Verses::ONE, 4..6)?;
+        ending_last(&mut repeats, Verses::TWO, 6..8)?;
+        // build
+        let repeats = repeats.build()?;
+        assert_eq!(repeats.repeats, vec![
+            Repeat { verse: Some(0), bars: 0..2 },
+            Repeat { verse: Some(0), bars: 2..4 },
+            Repeat { verse: Some(1), bars: 2..4 },
+            Repeat { verse: None, bars: 4..6 },
+            Repeat { verse: Some(1), bars: 0..2 },
+            Repeat { verse: Some(2), bars: 2..4 },
+            Repeat { verse: Some(3), bars: 2..4 },
+            Repeat { verse: None, bars: 6..8 },
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_repeat_in_ending() -> Result<()> {
+        let mut repeats = new_repeat_start(2)?;
+        repeats.next(3)?;
+        repeats.ending_start(Verses::ONE)?;
+        repeats.forward(1024);
+        normal(&mut repeats, 3..4)?;
+        repeat(&mut repeats, 4..6)?;
+        normal(&mut repeats, 6..8)?;
+        repeats.ending_end(Verses::ONE, false)?;
+        repeats.repeat_end()?;
+        ending_last(&mut repeats, Verses::TWO, 8..10)?;
+        // build
+        let repeats = repeats.build()?;
+        assert_eq!(repeats.repeats, vec![
+            Repeat { verse: Some(0), bars: 0..2 },
+            Repeat { verse: None, bars: 2..4 },
+            Repeat { verse: Some(0), bars: 4..6 },
+            Repeat { verse: Some(1), bars: 4..6 },
+            Repeat { verse: None, bars: 6..8 },
+            Repeat { verse: Some(1), bars: 0..2 },
+            Repeat { verse: None, bars: 8..10 },
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ending_of_repeats() -> Result<()> {
+        let mut repeats = new_repeat_start(2)?;
+        repeats.next(3)?;
+        repeats.ending_start(Verses::ONE)?;
+        repeats.repeat_start()?;
+        repeats.forward(1024);
+        normal(&mut repeats, 3..4)?;
+        repeats.repeat_end()?;
+        repeat(&mut repeats, 4..6)?;
+        repeats.ending_end(Verses::ONE, false)?;
+        repeats.repeat_end()?;
+        ending_last(&mut repeats, Verses::TWO, 6..8)?;
+        // build
+        let repeats = repeats.build()?;
+        assert_eq!(repeats.repeats, vec![
+            Repeat { verse: Some(0), bars: 0..2 },
+            Repeat { verse: Some(0), bars: 2..4 },
+            Repeat { verse: Some(1), bars: 2..4 },
+            Repeat { verse: Some(0), bars: 4..6 },
+            Repeat { verse: Some(1), bars: 4..6 },
+            Repeat { verse: Some(1), bars: 0..2 },
+            Repeat { verse: None, bars: 6..8 },
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_second_ending_only() -> Result<()> {
+        let mut repeats = new_repeat(4)?;
+        ending_last(&mut repeats, Verses::TWO, 4..6)?;
+        normal(&mut repeats, 6..8)?;
+        // build
+        let repeats = repeats.build()?;
+        assert_eq!(repeats.repeats, vec![
+            Repeat { verse: Some(0), bars: 0..4 },
+            Repeat { verse: Some(1), bars: 0..4 },
+            Repeat { verse: None, bars: 4..6 },
+            Repeat { verse: None, bars: 6..8 },
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_roadmap() -> Result<()> {
+        let mut repeats = new(8)?;
+        repeat_start(&mut repeats, 8..16)?;
+        ending(&mut repeats, Verses::ONE, 16..18)?;
+        ending_last(&mut repeats, Verses::TWO, 18..20)?;
+        normal(&mut repeats, 20..40)?;
+        // build
+        let repeats = repeats.build()?;
+        let roadmap: Vec<String> = repeats.roadmap().iter()
+            .map(|segment| segment.to_string())
+            .collect();
+        assert_eq!(roadmap.join(", "), "1-8, 9-16 (v1), 17-18 (1st), 9-16 (v2), 19-20 (2nd), 21-40");
+        let outer: Vec<Option<usize>> = repeats.roadmap().iter()
+            .map(|segment| segment.repeat)
+            .collect();
+        assert_eq!(outer, vec![None, Some(0), Some(0), Some(0), Some(0), None]);
+        Ok(())
+    }
+
     #[test]
     fn test_repeat_repeat() -> Result<()> {
         let mut repeats = new_repeat(4)?;
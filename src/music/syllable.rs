@@ -4,9 +4,12 @@ use anyhow::{
 };
 
 use super::{
+    Mark,
     Part,
+    Phrasing,
     //Phrase,
     Repeats,
+    Source,
     //Word,
 };
 
@@ -44,11 +47,14 @@ pub struct Syllable<'xml> {
     pub end: usize,
     pub kind: Kind,
     pub text: &'xml str,
+    pub source: Source,
 }
 
 #[derive(Debug)]
 struct Bar<'xml> {
     verse: [Vec<Syllable<'xml>>; 6],
+    /// Marks of the score at ticks into the bar
+    marks: Vec<(usize, Mark)>,
 }
 
 impl<'xml> Bar<'xml> {
@@ -62,6 +68,7 @@ impl<'xml> Bar<'xml> {
                 Vec::new(),
                 Vec::new(),
             ],
+            marks: Vec::new(),
         }
     }
 
@@ -73,11 +80,14 @@ impl<'xml> Bar<'xml> {
 
     fn iter(&self, verse: Option<usize>, tick: usize) -> Iter<'_> {
         if let Some(verse) = verse {
-            if verse > 0 && !self.verse[verse].is_empty() {
+            // passes beyond the verses of lyrics sing the first verse
+            let syllables = self.verse.get(verse)
+                .filter(|syllables| verse > 0 && !syllables.is_empty());
+            if let Some(syllables) = syllables {
                 return Iter {
                     end: 0,
                     common: Some(self.verse[0].iter()),
-                    syllables: self.verse[verse].iter(),
+                    syllables: syllables.iter(),
                     tick,
                 };
             }
@@ -110,7 +120,13 @@ impl<'xml> Builder<'xml> {
         }
     }
 
-    pub fn part_end(&mut self, repeats: &Repeats) -> Option<Part> {
+    /// Words of the part as played, or of a single verse as written
+    pub fn part_end(
+        &mut self,
+        repeats: &Repeats,
+        verse: Option<usize>,
+        phrasing: &Phrasing,
+    ) -> Option<Part> {
         let mut bar = std::mem::replace(&mut self.bar, Bar::new());
         bar.sort();
         self.bars.push(bar);
@@ -121,12 +137,29 @@ impl<'xml> Builder<'xml> {
                 &mut self.bars,
                 Vec::with_capacity(self.bar_count),
             );
-            let mut builder = super::word::Builder::new();
-            for repeat in repeats.bars() {
-                for syllable in bars[repeat.index]
-                    .iter(repeat.verse, repeat.tick)
-                {
-                    builder.syllable(syllable);
+            let mut builder = super::word::Builder::new(phrasing);
+            if let Some(verse) = verse {
+                for bar in repeats.written() {
+                    builder.bar(repeats.first_bar_number() + bar.index);
+                    for (tick, mark) in &bars[bar.index].marks {
+                        builder.mark(*mark, bar.tick + tick);
+                    }
+                    for syllable in bars[bar.index].iter(Some(verse), bar.tick) {
+                        builder.syllable(syllable);
+                    }
+                }
+            } else {
+                for repeat in repeats.bars() {
+                    builder.bar(repeats.first_bar_number() + repeat.index);
+                    builder.verse(repeat.verse);
+                    for (tick, mark) in &bars[repeat.index].marks {
+                        builder.mark(*mark, repeat.tick + tick);
+                    }
+                    for syllable in bars[repeat.index]
+                        .iter(repeat.verse, repeat.tick)
+                    {
+                        builder.syllable(syllable);
+                    }
                 }
             }
             Some(builder.build())
@@ -146,6 +179,10 @@ impl<'xml> Builder<'xml> {
         }
     }
 
+    pub fn mark(&mut self, mark: Mark, tick: usize) {
+        self.bar.marks.push((tick, mark));
+    }
+
     pub fn lyric(
         &mut self,
         _voice: usize,
@@ -176,6 +213,7 @@ impl<'xml> std::iter::Iterator for Iter<'xml> {
                 end: syllable.end + self.tick,
                 kind: syllable.kind,
                 text: syllable.text,
+                source: syllable.source,
             })
         } else if let Some(mut common) = self.common.take() {
             while let Some(syllable) = common.next() {
@@ -187,6 +225,7 @@ impl<'xml> std::iter::Iterator for Iter<'xml> {
                         end: syllable.end + self.tick,
                         kind: syllable.kind,
                         text: syllable.text,
+                        source: syllable.source,
                     });
                 }
             }
@@ -7,10 +7,10 @@ use anyhow::bail;
 pub struct Verses(usize);
 
 impl Verses {
-    #[cfg(test)]
     pub const ONE: Verses = Verses(1);
     #[cfg(test)]
     pub const ONE_THREE: Verses = Verses(5);
+    #[cfg(test)]
     pub const TWO: Verses = Verses(2);
     #[cfg(test)]
     pub const TWO_FOUR: Verses = Verses(10);
@@ -24,13 +24,28 @@ impl Verses {
     //     self.0 == 1 << verse
     // }
 
-    pub fn to_single(&self) -> Option<usize> {
-        let verse = self.0.trailing_zeros();
-        if self.0 == 1 << verse {
-            Some(verse as usize)
-        } else {
-            None
+    pub fn contains(&self, verse: usize) -> bool {
+        verse < usize::BITS as usize && self.0 & (1 << verse) != 0
+    }
+}
+
+/// Ordinals of the verses, like `1st, 3rd`
+impl std::fmt::Display for Verses {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, verse) in self.clone().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            let number = verse + 1;
+            let suffix = match (number % 10, number % 100) {
+                (1, n) if n != 11 => "st",
+                (2, n) if n != 12 => "nd",
+                (3, n) if n != 13 => "rd",
+                _ => "th",
+            };
+            write!(f, "{number}{suffix}")?;
         }
+        Ok(())
     }
 }
 
@@ -83,6 +98,13 @@ mod tests {
         assert_eq!(&verses, vs);
     }
 
+    #[test]
+    fn test_display() {
+        assert_eq!(Verses::ONE.to_string(), "1st");
+        assert_eq!(Verses::ONE_THREE.to_string(), "1st, 3rd");
+        assert_eq!(Verses::TWO_FOUR.to_string(), "2nd, 4th");
+    }
+
     #[test]
     fn test_parse_and_collect() {
         parse_and_collect("1", &[1]);
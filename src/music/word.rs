@@ -1,23 +1,33 @@
 
 use super::{
+    Lyric,
+    Mark,
     Part,
     Phrase,
+    Phrasing,
     syllable::Kind,
     Syllable,
     Word,
-    MINIM,
+    CROTCHET,
 };
 
-pub struct Builder {
+pub struct Builder<'phrasing> {
+    phrasing: &'phrasing Phrasing,
+    /// Ticks of marks ending a phrase before the next word
+    marks: Vec<usize>,
     phrases: Vec<Phrase>,
     phrase: Phrase,
     word: Option<Word>,
     in_group: bool,
+    bar: usize,
+    verse: Option<usize>,
 }
 
-impl Builder {
-    pub fn new() -> Self {
+impl<'phrasing> Builder<'phrasing> {
+    pub fn new(phrasing: &'phrasing Phrasing) -> Self {
         Builder {
+            phrasing,
+            marks: Vec::new(),
             phrases: Vec::new(),
             phrase: Phrase {
                 start: 0,
@@ -26,10 +36,30 @@ impl Builder {
             },
             word: None,
             in_group: false,
+            bar: 0,
+            verse: None,
+        }
+    }
+
+    pub fn bar(&mut self, bar: usize) {
+        self.bar = bar;
+    }
+
+    pub fn verse(&mut self, verse: Option<usize>) {
+        self.verse = verse;
+    }
+
+    pub fn mark(&mut self, mark: Mark, tick: usize) {
+        if self.phrasing.marks.contains(&mark) {
+            self.marks.push(tick);
         }
     }
 
     pub fn syllable(&mut self, syllable: Syllable) {
+        let lyric = Lyric {
+            source: syllable.source,
+            text: String::from(syllable.text),
+        };
         match syllable.kind {
             Kind::Single => {
                 if let Some(word) = self.word.take() {
@@ -39,6 +69,9 @@ impl Builder {
                     start: syllable.start,
                     end: syllable.end,
                     text: String::from(syllable.text),
+                    bar: self.bar,
+                    verse: self.verse,
+                    lyrics: vec![vec![lyric]],
                 });
             }
 
@@ -50,6 +83,9 @@ impl Builder {
                     start: syllable.start,
                     end: syllable.end,
                     text: String::from(syllable.text),
+                    bar: self.bar,
+                    verse: self.verse,
+                    lyrics: vec![vec![lyric]],
                 });
             }
 
@@ -57,11 +93,15 @@ impl Builder {
                 if let Some(word) = &mut self.word {
                     word.end = syllable.end;
                     word.text.push_str(syllable.text);
+                    word.lyrics[0].push(lyric);
                 } else {
                     self.word = Some(Word {
                         start: syllable.start,
                         end: syllable.end,
                         text: String::from(syllable.text),
+                        bar: self.bar,
+                        verse: self.verse,
+                        lyrics: vec![vec![lyric]],
                     });
                 },
 
@@ -69,12 +109,16 @@ impl Builder {
                 if let Some(mut word) = self.word.take() {
                     word.end = syllable.end;
                     word.text.push_str(syllable.text);
+                    word.lyrics[0].push(lyric);
                     self.word(word);
                 } else {
                     self.word(Word {
                         start: syllable.start,
                         end: syllable.end,
                         text: String::from(syllable.text),
+                        bar: self.bar,
+                        verse: self.verse,
+                        lyrics: vec![vec![lyric]],
                     });
                 },
         }
@@ -86,6 +130,9 @@ impl Builder {
                 start: word.start,
                 end: word.start + 1,
                 text: String::from(left),
+                bar: word.bar,
+                verse: word.verse,
+                lyrics: word.lyrics.clone(),
             });
             word.start += 1;
             word.text = String::from(right);
@@ -94,24 +141,32 @@ impl Builder {
     }
 
     fn word_single(&mut self, word: Word) {
-        let group_start = word.text.starts_with('[');
-        let group_end = word.text.ends_with(']');
+        let group_start = self.phrasing.groups && word.text.starts_with('[');
+        let group_end = self.phrasing.groups && word.text.ends_with(']');
         let is_end = if self.in_group {
             group_end
         } else {
-            word.text.ends_with('.') || word.text.ends_with('!')
+            word.text.ends_with(|c| self.phrasing.ends.contains(c))
         };
+        // a mark of the score between the previous word and this
+        let marked = self.marks.iter().any(|tick| word.start >= *tick);
+        self.marks.retain(|tick| word.start < *tick);
         if !self.phrase.words.is_empty() {
             let new_phrase = if self.in_group {
                 false
             } else {
                 // start new phrase at a capital letter
                 // and there is a rest between the previous word
-                let rest_then_capital = is_capital(&word.text) && word.start > self.phrase.end;
+                let rest_then_capital = self.phrasing.capitals
+                    && is_capital(&word.text)
+                    && word.start > self.phrase.end;
                 // OR
                 // start new phrase after a big rest (minum)
-                let big_rest = word.start >= self.phrase.end + MINIM;
-                rest_then_capital || big_rest
+                let big_rest = self.phrasing.rest
+                    .is_some_and(|rest| word.start >= self.phrase.end + rest * CROTCHET);
+                // OR
+                // start new phrase after a mark of the score
+                rest_then_capital || big_rest || marked
             };
             if group_start || new_phrase {
                 self.phrases.push(std::mem::replace(&mut self.phrase, Phrase {
@@ -0,0 +1,119 @@
+use anyhow::{bail, Context, Result};
+use roxmltree::Document;
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+
+const CONTAINER: &str = "META-INF/container.xml";
+
+/// Largest entry read from an archive once decompressed, so a small
+/// upload cannot expand without bound
+const LIMIT: u64 = 64 * 1024 * 1024;
+
+/// MUSICXML of a compressed MXL archive, the root file named by its
+/// container, or else the only MUSICXML it holds.
+pub fn read(mxl: &[u8]) -> Result<String> {
+    let mut archive = ZipArchive::new(Cursor::new(mxl))
+        .context("Reading MXL archive")?;
+
+    let path = match read_entry(&mut archive, CONTAINER, LIMIT) {
+        Ok(container) => root_file(&container)?,
+        Err(_) => only_music(&archive)?,
+    };
+    read_entry(&mut archive, &path, LIMIT)
+}
+
+fn read_entry(archive: &mut ZipArchive<Cursor<&[u8]>>, path: &str, limit: u64) -> Result<String> {
+    let file = archive.by_name(path)
+        .with_context(|| format!("Reading {path} from MXL archive"))?;
+    if file.size() > limit {
+        bail!("{path} in MXL archive is larger than {limit} bytes");
+    }
+    let mut text = String::new();
+    // the size is only what the archive claims
+    file.take(limit + 1).read_to_string(&mut text)
+        .with_context(|| format!("Reading {path} from MXL archive"))?;
+    if text.len() as u64 > limit {
+        bail!("{path} in MXL archive is larger than {limit} bytes");
+    }
+    Ok(text)
+}
+
+fn root_file(container: &str) -> Result<String> {
+    let doc = Document::parse(container)
+        .with_context(|| format!("Reading {CONTAINER}"))?;
+    let root_file = doc.descendants()
+        .find(|node| node.has_tag_name("rootfile"))
+        .and_then(|node| node.attribute("full-path"));
+    match root_file {
+        Some(path) => Ok(String::from(path)),
+        None => bail!("No <rootfile full-path> in {CONTAINER}"),
+    }
+}
+
+fn only_music(archive: &ZipArchive<Cursor<&[u8]>>) -> Result<String> {
+    let musics: Vec<&str> = archive.file_names()
+        .filter(|name| !name.starts_with("META-INF/"))
+        .filter(|name| name.ends_with(".musicxml") || name.ends_with(".xml"))
+        .collect();
+    match &musics[..] {
+        [music] => Ok(String::from(*music)),
+        [] => bail!("No MUSICXML in MXL archive"),
+        _ => bail!("No {CONTAINER} to choose between MUSICXML in MXL archive"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    fn archive(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, text) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(text.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_read_container() -> Result<()> {
+        let mxl = archive(&[
+            (CONTAINER, concat!(
+                "<container><rootfiles>",
+                "<rootfile full-path=\"score/Song.xml\"/>",
+                "</rootfiles></container>",
+            )),
+            ("score/Other.xml", "<other/>"),
+            ("score/Song.xml", "<score-partwise/>"),
+        ]);
+        assert_eq!(read(&mxl)?, "<score-partwise/>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_only_music() -> Result<()> {
+        let mxl = archive(&[
+            ("Song.musicxml", "<score-partwise/>"),
+        ]);
+        assert_eq!(read(&mxl)?, "<score-partwise/>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_entry_limit() -> Result<()> {
+        let mxl = archive(&[
+            ("Song.musicxml", "<score-partwise/>"),
+        ]);
+        let mut archive = ZipArchive::new(Cursor::new(&mxl[..]))?;
+        assert_eq!(read_entry(&mut archive, "Song.musicxml", 17)?, "<score-partwise/>");
+        assert!(read_entry(&mut archive, "Song.musicxml", 16).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_not_archive() {
+        assert!(read(b"<score-partwise/>").is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use clap::{
+    Parser,
+    ValueEnum,
+};
+use rocket::config::LogLevel;
+use std::net::{
+    IpAddr,
+    Ipv4Addr,
+    TcpListener,
+};
+use std::path::PathBuf;
+
+/// How many ports after the requested one to try when it is busy
+const PORT_TRIES: u16 = 100;
+
+/// Web server comparing lyrics between scripts (TXT) and music (MUSICXML)
+#[derive(Parser)]
+#[command(name = "lyric-check", version)]
+pub struct Options {
+    /// Folder of scripts and music to serve [default: current folder]
+    #[arg(env = "LYRIC_CHECK_ROOT", value_parser = folder)]
+    pub root: Option<PathBuf>,
+
+    /// Address to listen on
+    #[arg(long, env = "LYRIC_CHECK_ADDRESS", default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    pub address: IpAddr,
+
+    /// Port to listen on, or the next free port after it
+    #[arg(long, env = "LYRIC_CHECK_PORT", default_value_t = 7000)]
+    pub port: u16,
+
+    /// Do not open a web browser
+    #[arg(long, env = "LYRIC_CHECK_NO_BROWSER")]
+    pub no_browser: bool,
+
+    /// Largest script or music file that may be uploaded, in mebibytes
+    #[arg(long, env = "LYRIC_CHECK_UPLOAD_LIMIT", default_value_t = 8)]
+    pub upload_limit: u64,
+
+    /// Level of logging to the console
+    #[arg(long, env = "LYRIC_CHECK_LOG", value_enum, default_value_t = Log::Normal)]
+    pub log: Log,
+}
+
+#[derive(Clone, Copy)]
+#[derive(ValueEnum)]
+pub enum Log {
+    Off,
+    Critical,
+    Normal,
+    Debug,
+}
+
+impl From<Log> for LogLevel {
+    fn from(log: Log) -> Self {
+        match log {
+            Log::Off => LogLevel::Off,
+            Log::Critical => LogLevel::Critical,
+            Log::Normal => LogLevel::Normal,
+            Log::Debug => LogLevel::Debug,
+        }
+    }
+}
+
+impl Options {
+    /// First port, from the requested one, that is free to listen on
+    pub fn free_port(&self) -> Option<u16> {
+        (self.port..=self.port.saturating_add(PORT_TRIES))
+            .find(|port| TcpListener::bind((self.address, *port)).is_ok())
+    }
+}
+
+fn folder(arg: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(arg);
+    if path.is_dir() {
+        Ok(path)
+    } else {
+        Err(format!("`{arg}` is not a folder"))
+    }
+}
+
+/// URL for a browser to reach the server listening on `address`
+pub fn url(address: IpAddr, port: u16) -> String {
+    match address {
+        address if address.is_unspecified() || address.is_loopback() =>
+            format!("http://localhost:{port}"),
+
+        IpAddr::V4(address) =>
+            format!("http://{address}:{port}"),
+
+        IpAddr::V6(address) =>
+            format!("http://[{address}]:{port}"),
+    }
+}
@@ -0,0 +1,149 @@
+use std::path::{Component, Path, PathBuf};
+
+/// Root folder being served, confining every requested path within it
+pub struct Root {
+    path: PathBuf,
+}
+
+/// Reason a requested path is refused
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum Refused {
+    NotFound(PathBuf),
+    Forbidden(PathBuf),
+}
+
+impl std::fmt::Display for Refused {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Refused::NotFound(path) =>
+                write!(f, "Not found: {}", path.display()),
+
+            Refused::Forbidden(path) =>
+                write!(f, "Forbidden: {}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for Refused {}
+
+impl Root {
+    pub fn new(path: &Path) -> std::io::Result<Self> {
+        Ok(Root {
+            path: path.canonicalize()?,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Existing path of `relative` within the root, refusing any path
+    /// that is absolute, climbs out with `..`, or links outside the root.
+    pub fn resolve(&self, relative: &Path) -> Result<PathBuf, Refused> {
+        for component in relative.components() {
+            match component {
+                Component::Normal(_) | Component::CurDir =>
+                    (),
+
+                Component::ParentDir | Component::RootDir | Component::Prefix(_) =>
+                    return Err(Refused::Forbidden(relative.to_path_buf())),
+            }
+        }
+        let Ok(path) = self.path.join(relative).canonicalize() else {
+            return Err(Refused::NotFound(relative.to_path_buf()));
+        };
+        if path.starts_with(&self.path) {
+            Ok(path)
+        } else {
+            Err(Refused::Forbidden(relative.to_path_buf()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Temporary root folder containing `folder/song.txt` beside `outside.txt`
+    struct Temp {
+        path: PathBuf,
+        root: Root,
+    }
+
+    impl Temp {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir()
+                .join(format!("lyric-check-{}-{name}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(path.join("root").join("folder")).unwrap();
+            std::fs::write(path.join("root").join("folder").join("song.txt"), "").unwrap();
+            std::fs::write(path.join("outside.txt"), "").unwrap();
+            let root = Root::new(&path.join("root")).unwrap();
+            Temp {
+                path,
+                root,
+            }
+        }
+    }
+
+    impl Drop for Temp {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.path);
+        }
+    }
+
+    #[test]
+    fn test_resolve_inside() {
+        let temp = Temp::new("inside");
+        let song = temp.root.path().join("folder").join("song.txt");
+        assert_eq!(temp.root.resolve(Path::new("folder/song.txt")), Ok(song));
+        assert_eq!(temp.root.resolve(Path::new("")), Ok(temp.root.path().to_path_buf()));
+    }
+
+    #[test]
+    fn test_resolve_missing() {
+        let temp = Temp::new("missing");
+        assert_eq!(
+            temp.root.resolve(Path::new("folder/missing.txt")),
+            Err(Refused::NotFound(PathBuf::from("folder/missing.txt"))),
+        );
+    }
+
+    #[test]
+    fn test_resolve_parent() {
+        let temp = Temp::new("parent");
+        assert_eq!(
+            temp.root.resolve(Path::new("../outside.txt")),
+            Err(Refused::Forbidden(PathBuf::from("../outside.txt"))),
+        );
+        assert_eq!(
+            temp.root.resolve(Path::new("folder/../../outside.txt")),
+            Err(Refused::Forbidden(PathBuf::from("folder/../../outside.txt"))),
+        );
+    }
+
+    #[test]
+    fn test_resolve_absolute() {
+        let temp = Temp::new("absolute");
+        let outside = temp.path.join("outside.txt");
+        assert_eq!(
+            temp.root.resolve(&outside),
+            Err(Refused::Forbidden(outside.clone())),
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_link_outside() {
+        let temp = Temp::new("link");
+        std::os::unix::fs::symlink(
+            temp.path.join("outside.txt"),
+            temp.root.path().join("link.txt"),
+        ).unwrap();
+        assert_eq!(
+            temp.root.resolve(Path::new("link.txt")),
+            Err(Refused::Forbidden(PathBuf::from("link.txt"))),
+        );
+    }
+}
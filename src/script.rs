@@ -1,12 +1,31 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+
+use super::music::Phrase;
 
 #[derive(Debug)]
 pub enum Word {
     Heading(String),
-    Line(String),
+    Line {
+        number: String,
+        /// Line of the TXT (zero based) the number starts
+        index: usize,
+    },
     Word(String),
 }
 
+/// Script heading mapped to a portion of the music by the front matter
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct Mapping {
+    pub heading: String,
+    /// Numbers of the first and last bars
+    pub bars: Option<RangeInclusive<usize>>,
+    /// Pass through a repeat (zero based)
+    pub verse: Option<usize>,
+}
+
 enum State {
     Start,
     Digit,
@@ -20,7 +39,7 @@ enum State {
 pub fn read<'str>(txt: &'str str) -> Result<Vec<Word>> {
     let mut words = Vec::new();
 
-    for line in txt.lines() {
+    for (index, line) in txt.lines().enumerate().skip(front_matter_len(txt)) {
         let mut state = State::Start;
         let line = line
             .replace('\u{2019}', "\'")
@@ -45,7 +64,10 @@ pub fn read<'str>(txt: &'str str) -> Result<Vec<Word>> {
                 }
 
                 State::Dot => {
-                    words.push(Word::Line(String::from(&line[..i])));
+                    words.push(Word::Line {
+                        number: String::from(&line[..i]),
+                        index,
+                    });
                     if c == ' ' {
                         state = State::Space;
                     } else {
@@ -85,3 +107,211 @@ pub fn read<'str>(txt: &'str str) -> Result<Vec<Word>> {
     Ok(words)
 }
 
+/// Mappings of the front matter between `---` lines at the start of a
+/// TXT, like `Chorus: 17-24` or `Verse 2: 9-16 v2`
+pub fn front_matter(txt: &str) -> Result<Vec<Mapping>> {
+    let mut mappings = Vec::new();
+    let count = front_matter_len(txt);
+    for line in txt.lines().take(count.saturating_sub(1)).skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some((heading, portion)) = line.rsplit_once(':') else {
+            bail!("Expecting `<heading>: <bars> v<verse>` in front matter, not `{line}`");
+        };
+        let mut mapping = Mapping {
+            heading: String::from(heading.trim()),
+            bars: None,
+            verse: None,
+        };
+        for part in portion.split_whitespace() {
+            if let Some(verse) = part.strip_prefix(['v', 'V']) {
+                match verse.parse::<usize>() {
+                    Ok(verse) if verse > 0 =>
+                        mapping.verse = Some(verse - 1),
+                    _ =>
+                        bail!("Unexpected verse `{part}` in front matter `{line}`"),
+                }
+            } else {
+                let (first, last) = part.split_once('-').unwrap_or((part, part));
+                match (first.parse::<usize>(), last.parse::<usize>()) {
+                    (Ok(first), Ok(last)) if first <= last =>
+                        mapping.bars = Some(first..=last),
+                    _ =>
+                        bail!("Unexpected bars `{part}` in front matter `{line}`"),
+                }
+            }
+        }
+        if mapping.bars.is_none() && mapping.verse.is_none() {
+            bail!("Expecting bars or verse in front matter `{line}`");
+        }
+        mappings.push(mapping);
+    }
+    Ok(mappings)
+}
+
+/// Number of lines of the front matter, including both `---` lines
+fn front_matter_len(txt: &str) -> usize {
+    let mut lines = txt.lines();
+    if lines.next().map(str::trim_end) != Some("---") {
+        return 0;
+    }
+    match lines.position(|line| line.trim_end() == "---") {
+        Some(position) => position + 2,
+        None => 0,
+    }
+}
+
+/// Version of a TXT, to tell whether it changed since it was read
+pub fn version(txt: &str) -> String {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    txt.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Replace the line of a TXT (zero based), keeping its line ending
+pub fn edit(txt: &str, index: usize, text: &str) -> Result<String> {
+    if text.contains(['\r', '\n']) {
+        bail!("Expecting a single line, not `{text}`");
+    }
+    let mut edited = String::with_capacity(txt.len() + text.len());
+    let mut found = false;
+    for (i, line) in txt.split_inclusive('\n').enumerate() {
+        if i == index {
+            let content = line.trim_end_matches(['\r', '\n']);
+            edited.push_str(text);
+            edited.push_str(&line[content.len()..]);
+            found = true;
+        } else {
+            edited.push_str(line);
+        }
+    }
+    if !found {
+        bail!("No line {} in script", index + 1);
+    }
+    Ok(edited)
+}
+
+/// Draft a script from the phrases of music, a numbered line per phrase
+/// with a heading at the start of each verse, in the format read by `read`.
+pub fn draft(title: &str, phrases: &[Phrase]) -> String {
+    let mut txt = String::new();
+    writeln!(txt, " {title}").unwrap();
+    writeln!(txt, "{}", "=".repeat(2 + title.len())).unwrap();
+    writeln!(txt).unwrap();
+
+    let mut verse = None;
+    for (number, phrase) in phrases.iter().enumerate() {
+        if let Some(next) = phrase.words.first().and_then(|word| word.verse) {
+            if verse != Some(next) {
+                writeln!(txt, "Verse {}", next + 1).unwrap();
+                verse = Some(next);
+            }
+        }
+        write!(txt, "{}.", number + 1).unwrap();
+        for word in &phrase.words {
+            write!(txt, " {}", word.text).unwrap();
+        }
+        writeln!(txt).unwrap();
+    }
+    txt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::music::Word as Music;
+
+    fn phrase(verse: Option<usize>, words: &[&str]) -> Phrase {
+        Phrase {
+            start: 0,
+            end: 0,
+            words: words.iter().map(|text| Music {
+                start: 0,
+                end: 0,
+                text: String::from(*text),
+                bar: 0,
+                verse,
+                lyrics: Vec::new(),
+            }).collect(),
+        }
+    }
+
+    #[test]
+    fn test_edit() -> Result<()> {
+        let txt = "Verse 1\r\n1. Helo world\r\n2. The end";
+        assert_eq!(edit(txt, 1, "1. Hello world")?, "Verse 1\r\n1. Hello world\r\n2. The end");
+        assert_eq!(edit(txt, 2, "2. The end!")?, "Verse 1\r\n1. Helo world\r\n2. The end!");
+        assert!(edit(txt, 3, "3. More").is_err());
+        assert!(edit(txt, 1, "1. Hello\n2. world").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_line_index() -> Result<()> {
+        let words = read("Song\n\n1. Hello\n2. World")?;
+        let indexes: Vec<usize> = words.iter()
+            .filter_map(|word| match word {
+                Word::Line { index, .. } => Some(*index),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(indexes, vec![2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_front_matter() -> Result<()> {
+        let txt = "---\nVerse 1: v1\nChorus: 17-24\nVerse 2: 9-16 v2\n---\nVerse 1\n1. Hello";
+        assert_eq!(front_matter(txt)?, vec![
+            Mapping { heading: String::from("Verse 1"), bars: None, verse: Some(0) },
+            Mapping { heading: String::from("Chorus"), bars: Some(17..=24), verse: None },
+            Mapping { heading: String::from("Verse 2"), bars: Some(9..=16), verse: Some(1) },
+        ]);
+        let words = read(txt)?;
+        assert!(matches!(&words[0], Word::Heading(heading) if heading == "Verse 1"));
+        assert!(matches!(&words[1], Word::Line { index: 6, .. }));
+
+        assert_eq!(front_matter("---\nVerse 1\n1. Hello")?, vec![]);
+        assert!(front_matter("---\nChorus\n---\n").is_err());
+        assert!(front_matter("---\nChorus: 24-17\n---\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_draft_reads_back() -> Result<()> {
+        let phrases = vec![
+            phrase(Some(0), &["Hello", "world."]),
+            phrase(None, &["La", "la"]),
+            phrase(Some(1), &["Goodbye", "world."]),
+            phrase(None, &["The", "end!"]),
+        ];
+        let txt = draft("Song", &phrases);
+        assert_eq!(txt, concat!(
+            " Song\n",
+            "======\n",
+            "\n",
+            "Verse 1\n",
+            "1. Hello world.\n",
+            "2. La la\n",
+            "Verse 2\n",
+            "3. Goodbye world.\n",
+            "4. The end!\n",
+        ));
+
+        let words: Vec<String> = read(&txt)?.into_iter()
+            .filter_map(|word| match word {
+                Word::Word(text) => Some(text),
+                _ => None,
+            })
+            .collect();
+        let texts: Vec<String> = phrases.iter()
+            .flat_map(|phrase| phrase.words.iter())
+            .map(|word| word.text.clone())
+            .collect();
+        assert_eq!(words, texts);
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use rocket::{
+    fairing::AdHoc,
+    tokio::{
+        self,
+        sync::broadcast,
+        time,
+    },
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How often the root folder is scanned for changes
+const INTERVAL: Duration = Duration::from_secs(1);
+
+/// Broadcast of scripts and music changed under the root folder
+pub struct Watch {
+    sender: broadcast::Sender<PathBuf>,
+}
+
+impl Watch {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(64);
+        Watch {
+            sender,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<PathBuf> {
+        self.sender.subscribe()
+    }
+
+    /// Scan the root folder once the server is running
+    pub fn fairing(root: PathBuf) -> AdHoc {
+        AdHoc::on_liftoff("Watch", |rocket| Box::pin(async move {
+            let Some(watch) = rocket.state::<Watch>() else {
+                return;
+            };
+            let sender = watch.sender.clone();
+            tokio::spawn(async move {
+                let mut files = HashMap::new();
+                scan(&root, &mut files).await;
+                let mut interval = time::interval(INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let mut next = HashMap::with_capacity(files.len());
+                    scan(&root, &mut next).await;
+                    for (path, modified) in &next {
+                        if files.get(path) != Some(modified) {
+                            let _ = sender.send(path.clone());
+                        }
+                    }
+                    for path in files.keys() {
+                        if !next.contains_key(path) {
+                            let _ = sender.send(path.clone());
+                        }
+                    }
+                    files = next;
+                }
+            });
+        }))
+    }
+}
+
+async fn scan(root: &Path, files: &mut HashMap<PathBuf, SystemTime>) {
+    let mut folders = vec![root.to_path_buf()];
+    while let Some(folder) = folders.pop() {
+        let Ok(mut dir) = tokio::fs::read_dir(&folder).await else {
+            continue;
+        };
+        while let Ok(Some(entry)) = dir.next_entry().await {
+            let Ok(meta) = entry.metadata().await else {
+                continue;
+            };
+            let path = entry.path();
+            if meta.is_dir() {
+                folders.push(path);
+            } else if is_watched(&path) {
+                if let Ok(modified) = meta.modified() {
+                    files.insert(path, modified);
+                }
+            }
+        }
+    }
+}
+
+fn is_watched(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|extension| extension.to_str()),
+        Some("txt" | "musicxml"),
+    )
+}